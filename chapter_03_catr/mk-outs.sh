@@ -23,8 +23,68 @@ done
 cat    $ALL > $OUT_DIR/all.out
 cat -n $ALL > $OUT_DIR/all.n.out
 cat -b $ALL > $OUT_DIR/all.b.out
+for FILE in $ALL; do cat -n $FILE; done > $OUT_DIR/all.n.per-file.out
+for FILE in $ALL; do cat -b $FILE; done > $OUT_DIR/all.b.per-file.out
+
+nl -ba -w3 -s': ' -v5 -i2 -nrz $BUSTLE > $OUT_DIR/$(basename $BUSTLE).nl-rz.out
+nl -ba -nln -v-3 $BUSTLE > $OUT_DIR/$(basename $BUSTLE).nl-ln.out
+# nl pads unnumbered lines, cat leaves them alone
+nl -bp'^The' $BUSTLE | sed -E 's/^ {7}//' > $OUT_DIR/$(basename $BUSTLE).nl-p.out
 
 cat    < $BUSTLE > $OUT_DIR/$(basename $BUSTLE).stdin.out
 cat -n < $BUSTLE > $OUT_DIR/$(basename $BUSTLE).n.stdin.out
 cat -b < $BUSTLE > $OUT_DIR/$(basename $BUSTLE).b.stdin.out
 
+
+LATIN1="$ROOT/latin1.bin"
+BASENAME=$(basename "$LATIN1")
+for FLAGS in "" -n -b -E -s -ns -bE; do
+    SUFFIX=${FLAGS:+.${FLAGS#-}}
+    cat $FLAGS $LATIN1 > ${OUT_DIR}/${BASENAME}${SUFFIX}.out
+done
+cat -s < $LATIN1 > $OUT_DIR/${BASENAME}.s.stdin.out
+cat -n $LATIN1 $FOX > $OUT_DIR/${BASENAME}.$(basename $FOX).n.out
+
+CONTROL="$ROOT/control.bin"
+BASENAME=$(basename "$CONTROL")
+for FLAG in v T A e t vE; do
+    cat -$FLAG $CONTROL > ${OUT_DIR}/${BASENAME}.${FLAG}.out
+done
+
+# fox-bustle.txt.{gz,bz2,xz,zst} each hold two members, fox and then bustle
+cat $FOX $BUSTLE > $OUT_DIR/fox-bustle.out
+cat -n $FOX $BUSTLE > $OUT_DIR/fox-bustle.n.out
+
+# --range selections, built from the numbered output of each file
+cat -n $BUSTLE | sed -n '2,4p' > $OUT_DIR/$(basename $BUSTLE).range2-4.n.out
+cat -n $BUSTLE | tail -n 3 > $OUT_DIR/$(basename $BUSTLE).range-3.n.out
+sed -n '1,3p;6,8p' < $BUSTLE > $OUT_DIR/$(basename $BUSTLE).range-mixed.stdin.out
+for FILE in $BUSTLE $SPIDERS; do
+    cat -n $FILE | sed -n '1,3p;4,${$p}'
+done > $OUT_DIR/all.ranges.n.out
+
+# --hex dumps match xxd
+xxd $CONTROL > $OUT_DIR/$(basename $CONTROL).hex.out
+xxd -c 5 -g 3 $CONTROL > $OUT_DIR/$(basename $CONTROL).hex-c5-g3.out
+# each input is dumped on its own
+{ xxd -g 0 $FOX; xxd -g 0 $LATIN1; } > $OUT_DIR/fox-latin1.hex-g0.out
+{ xxd -c 5 $FOX; xxd -c 5 $CONTROL; } > $OUT_DIR/fox-control.hex-c5.out
+
+# character set conversion
+BUSTLE16="$ROOT/the-bustle.utf16le.txt"
+CREME="$ROOT/creme-brulee.latin1.txt"
+iconv -f utf-16 -t utf-8 $BUSTLE16 | cat -nE > $OUT_DIR/$(basename $BUSTLE16).nE.out
+iconv -f latin1 -t utf-8 $CREME | cat -n > $OUT_DIR/$(basename $CREME).n.out
+iconv -f utf-8 -t utf-16be $BUSTLE > $OUT_DIR/$(basename $BUSTLE).utf16be.out
+
+# -z works like plain cat on a stream with NUL and newline swapped
+RECORDS="$ROOT/records.bin"
+for FLAGS in n b s ns bsE; do
+    tr '\0\n' '\n\0' < $RECORDS | cat -$FLAGS | tr '\0\n' '\n\0' \
+        > $OUT_DIR/$(basename $RECORDS).z${FLAGS}.out
+done
+
+# cat has no --squeeze-max or --whitespace-blank, so the other
+# blanks.txt.*.out files were checked by hand
+BLANKS="$ROOT/blanks.txt"
+cat -s $BLANKS > $OUT_DIR/$(basename $BLANKS).s.out
@@ -0,0 +1,222 @@
+use encoding_rs::{
+    Decoder, DecoderResult, Encoder, EncoderResult, Encoding, UTF_16BE, UTF_16LE, UTF_8,
+};
+use std::io::{self, BufRead, Read, Write};
+
+// the character set of the input: either a named one, or `auto`, which
+// follows a byte order mark when there is one and assumes UTF-8 otherwise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEncoding {
+    Auto,
+    Named(&'static Encoding),
+}
+
+pub fn input_encoding(label: &str) -> Option<InputEncoding> {
+    match label {
+        "auto" => Some(InputEncoding::Auto),
+        _ => output_encoding(label).map(InputEncoding::Named),
+    }
+}
+
+pub fn output_encoding(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.as_bytes())
+}
+
+// decodes its input into UTF-8; malformed sequences become U+FFFD unless
+// `strict` is set, in which case they are an error
+pub struct DecodeReader<R: BufRead> {
+    input: R,
+    decoder: Decoder,
+    strict: bool,
+    decoded: Vec<u8>,
+    consumed: usize,
+    done: bool,
+}
+
+impl<R: BufRead> DecodeReader<R> {
+    pub fn new(input: R, encoding: InputEncoding, strict: bool) -> Self {
+        let decoder = match encoding {
+            InputEncoding::Auto => UTF_8.new_decoder(),
+            InputEncoding::Named(encoding) => encoding.new_decoder_with_bom_removal(),
+        };
+        DecodeReader {
+            input,
+            decoder,
+            strict,
+            decoded: Vec::new(),
+            consumed: 0,
+            done: false,
+        }
+    }
+
+    // decodes whatever the input has buffered, returning false at the end
+    fn decode_chunk(&mut self) -> io::Result<bool> {
+        if self.done {
+            return Ok(false);
+        }
+        let src = self.input.fill_buf()?;
+        let last = src.is_empty();
+        let needed = self
+            .decoder
+            .max_utf8_buffer_length(src.len())
+            .ok_or_else(|| io::Error::other("input chunk too large to decode"))?;
+        self.decoded.resize(needed, 0);
+        self.consumed = 0;
+        let (read, written) = if self.strict {
+            let (result, read, written) =
+                self.decoder
+                    .decode_to_utf8_without_replacement(src, &mut self.decoded, last);
+            if let DecoderResult::Malformed(..) = result {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("malformed {} input", self.decoder.encoding().name()),
+                ));
+            }
+            (read, written)
+        } else {
+            let (_, read, written, _) = self.decoder.decode_to_utf8(src, &mut self.decoded, last);
+            (read, written)
+        };
+        self.decoded.truncate(written);
+        self.input.consume(read);
+        self.done = last;
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.consumed == self.decoded.len() {
+            if !self.decode_chunk()? {
+                return Ok(0);
+            }
+        }
+        let available = &self.decoded[self.consumed..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consumed += n;
+        Ok(n)
+    }
+}
+
+// encodes the UTF-8 written to it into another character set. Bytes that
+// are not UTF-8 and characters the target cannot represent are written as
+// `?`, or are an error when `strict` is set.
+pub struct EncodeWriter<W: Write> {
+    out: W,
+    encoding: &'static Encoding,
+    encoder: Encoder,
+    strict: bool,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    pub fn new(out: W, encoding: &'static Encoding, strict: bool) -> Self {
+        EncodeWriter {
+            out,
+            encoding,
+            encoder: encoding.new_encoder(),
+            strict,
+            pending: Vec::new(),
+        }
+    }
+
+    // deals with a character cut off at the very end of the output
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.invalid()?;
+        }
+        self.encode("", true)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn invalid(&mut self) -> io::Result<()> {
+        if self.strict {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "output is not valid UTF-8",
+            ));
+        }
+        self.encode("?", false)
+    }
+
+    fn encode(&mut self, text: &str, last: bool) -> io::Result<()> {
+        // encoding_rs decodes UTF-16 but will not encode it, so that is done here
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            let mut bytes = Vec::with_capacity(text.len() * 2);
+            for unit in text.encode_utf16() {
+                if self.encoding == UTF_16LE {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            return self.out.write_all(&bytes);
+        }
+        let mut text = text;
+        let mut bytes = vec![
+            0;
+            self.encoder
+                .max_buffer_length_from_utf8_without_replacement(text.len())
+                .unwrap_or(text.len() * 4)
+                .max(16)
+        ];
+        loop {
+            let (result, read, written) =
+                self.encoder
+                    .encode_from_utf8_without_replacement(text, &mut bytes, last);
+            self.out.write_all(&bytes[..written])?;
+            text = &text[read..];
+            match result {
+                EncoderResult::InputEmpty => return Ok(()),
+                EncoderResult::OutputFull => continue,
+                EncoderResult::Unmappable(c) if self.strict => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("cannot encode {:?} as {}", c, self.encoding.name()),
+                    ))
+                }
+                EncoderResult::Unmappable(_) => self.out.write_all(b"?")?,
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        let pending = std::mem::take(&mut self.pending);
+        let mut rest = &pending[..];
+        while !rest.is_empty() {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    self.encode(text, false)?;
+                    rest = &[];
+                }
+                Err(err) => {
+                    let (valid, after) = rest.split_at(err.valid_up_to());
+                    // from_utf8 has just checked this prefix
+                    self.encode(std::str::from_utf8(valid).unwrap(), false)?;
+                    match err.error_len() {
+                        Some(len) => {
+                            self.invalid()?;
+                            rest = &after[len..];
+                        }
+                        // a character split across writes
+                        None => {
+                            self.pending = after.to_vec();
+                            rest = &[];
+                        }
+                    }
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::{self, BufRead, Read, Write};
+
+// how many bytes go on each line of a dump and how many of them are run
+// together in each hex group; a group of 0 means one group per line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexLayout {
+    pub cols: usize,
+    pub group: usize,
+}
+
+// renders everything written to it as an xxd-style dump: the offset, the
+// bytes as hex groups and a gutter showing the printable ones
+pub struct HexWriter<W: Write> {
+    out: W,
+    layout: HexLayout,
+    offset: u64,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> HexWriter<W> {
+    pub fn new(out: W, layout: HexLayout) -> Self {
+        HexWriter {
+            out,
+            layout,
+            offset: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    // writes out the last, possibly short, line of the dump
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.pending.is_empty() {
+            write_row(&mut self.out, self.layout, self.offset, &self.pending)?;
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn write_row(out: &mut impl Write, layout: HexLayout, offset: u64, row: &[u8]) -> io::Result<()> {
+    let group = match layout.group {
+        0 => layout.cols,
+        group => group,
+    };
+    let mut line = format!("{:08x}: ", offset);
+    for (i, byte) in row.iter().enumerate() {
+        if i > 0 && i % group == 0 {
+            line.push(' ');
+        }
+        line.push_str(&format!("{:02x}", byte));
+    }
+    // short rows are padded so that every gutter lines up
+    let width = |n: usize| n * 2 + n.saturating_sub(1) / group;
+    line.push_str(&" ".repeat(width(layout.cols) - width(row.len()) + 2));
+    line.extend(row.iter().map(|&byte| match byte {
+        0x20..=0x7e => byte as char,
+        _ => '.',
+    }));
+    line.push('\n');
+    out.write_all(line.as_bytes())
+}
+
+impl<W: Write> Write for HexWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let cols = self.layout.cols;
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let take = (cols - self.pending.len()).min(rest.len());
+            self.pending.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.pending.len() == cols {
+                write_row(&mut self.out, self.layout, self.offset, &self.pending)?;
+                self.offset += cols as u64;
+                self.pending.clear();
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// turns a dump made by HexWriter (or xxd) back into the bytes it shows.
+// Each line is an offset, a colon and hex digits in groups separated by
+// single spaces; two spaces in a row start the gutter, which is ignored.
+// Offsets ahead of the bytes produced so far are filled with zeros, which
+// are handed out as they are read rather than held in memory.
+pub struct HexReader<R: BufRead> {
+    input: R,
+    line: Vec<u8>,
+    gap: u64,
+    decoded: Vec<u8>,
+    consumed: usize,
+    position: u64,
+}
+
+impl<R: BufRead> HexReader<R> {
+    pub fn new(input: R) -> Self {
+        HexReader {
+            input,
+            line: Vec::new(),
+            gap: 0,
+            decoded: Vec::new(),
+            consumed: 0,
+            position: 0,
+        }
+    }
+
+    // decodes the next line of the dump, returning false at the end
+    fn decode_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        if self.input.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.decoded.clear();
+        self.consumed = 0;
+
+        let line = self.line.strip_suffix(b"\n").unwrap_or(&self.line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let colon = match line.iter().position(|&b| b == b':') {
+            Some(colon) => colon,
+            None if line.iter().all(u8::is_ascii_whitespace) => return Ok(true),
+            None => return Err(bad_dump(line)),
+        };
+        let offset = std::str::from_utf8(&line[..colon])
+            .ok()
+            .and_then(|offset| u64::from_str_radix(offset.trim(), 16).ok())
+            .ok_or_else(|| bad_dump(line))?;
+        self.gap = offset.saturating_sub(self.position);
+
+        let hex = line[colon + 1..].strip_prefix(b" ").unwrap_or(&line[colon + 1..]);
+        let mut digits = Vec::new();
+        let mut spaces = 0;
+        for &byte in hex {
+            match byte {
+                b' ' => {
+                    spaces += 1;
+                    if spaces == 2 {
+                        break;
+                    }
+                }
+                _ if byte.is_ascii_hexdigit() => {
+                    spaces = 0;
+                    digits.push(byte);
+                }
+                _ => break,
+            }
+        }
+        if digits.len() % 2 != 0 {
+            return Err(bad_dump(line));
+        }
+        for pair in digits.chunks(2) {
+            let pair = std::str::from_utf8(pair).map_err(|_| bad_dump(line))?;
+            let byte = u8::from_str_radix(pair, 16).map_err(|_| bad_dump(line))?;
+            self.decoded.push(byte);
+        }
+        self.position += self.gap + self.decoded.len() as u64;
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Read for HexReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.gap == 0 && self.consumed == self.decoded.len() {
+            if !self.decode_line()? {
+                return Ok(0);
+            }
+        }
+        if self.gap > 0 {
+            let n = buf.len().min(self.gap.try_into().unwrap_or(usize::MAX));
+            buf[..n].fill(0);
+            self.gap -= n as u64;
+            return Ok(n);
+        }
+        let available = &self.decoded[self.consumed..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consumed += n;
+        Ok(n)
+    }
+}
+
+fn bad_dump(line: &[u8]) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid hex dump line: {}", String::from_utf8_lossy(line)),
+    )
+}
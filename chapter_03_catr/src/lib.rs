@@ -1,64 +1,581 @@
+mod encoding;
+mod hex;
+mod pretty;
+
+use crate::encoding::{DecodeReader, EncodeWriter, InputEncoding};
+use crate::hex::{HexLayout, HexReader, HexWriter};
+use crate::pretty::PrettyPrinter;
+use crate::Numbering::*;
+use bzip2::bufread::MultiBzDecoder;
 use clap::{App, Arg};
+use encoding_rs::Encoding;
+use flate2::bufread::MultiGzDecoder;
+use regex::bytes::Regex;
+use std::collections::VecDeque;
 use std::error::Error;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::mem;
+use std::slice;
+#[cfg(unix)]
+use std::os::unix::{fs::MetadataExt, io::AsFd};
+use xz2::bufread::XzDecoder;
+
+#[derive(Debug)]
+pub enum Numbering {
+    Never,
+    All,
+    NonBlank,
+    Matching(Regex),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum NumberFormat {
+    Left,
+    Right,
+    RightZero,
+}
+
+// 1-based line positions, negative ones counting back from the last line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineRange {
+    start: i64,
+    end: i64,
+}
 
 #[derive(Debug)]
 pub struct Config {
     files: Vec<String>,
-    number_lines: bool,
-    number_nonblank_lines: bool,
+    numbering: Numbering,
+    number_width: usize,
+    number_separator: String,
+    number_start: i64,
+    number_increment: i64,
+    number_format: NumberFormat,
     show_ends: bool,
-    squeeze_blank: bool,
+    show_tabs: bool,
+    show_nonprinting: bool,
+    squeeze_blank: Option<usize>,
+    whitespace_blank: bool,
+    number_per_file: bool,
+    decompress: bool,
+    ranges: Vec<LineRange>,
+    terminator: u8,
+    hex: Option<HexLayout>,
+    hex_reverse: bool,
+    from_encoding: Option<InputEncoding>,
+    to_encoding: Option<&'static Encoding>,
+    strict_encoding: bool,
+    pretty: bool,
+    pager: bool,
+}
+
+// numbering and blank-line state, carried from one input to the next unless
+// --number-per-file asks for every file to start afresh
+#[derive(Debug)]
+struct LineState {
+    line_number: i64,
+    blank_run: usize,
+    at_line_start: bool,
+}
+
+impl Default for LineState {
+    fn default() -> Self {
+        LineState {
+            line_number: 0,
+            blank_run: 0,
+            at_line_start: true,
+        }
+    }
 }
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
+const BUF_SIZE: usize = 128 * 1024;
+
 pub fn run(config: Config) -> MyResult<()> {
-    for filename in config.files {
-        match open(&filename) {
+    // anything but a terminal gets exactly what plain mode would produce
+    if config.pretty && io::stdout().is_terminal() {
+        return show_pretty(&config);
+    }
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut state = LineState::default();
+    match config.hex {
+        // each input is dumped on its own, starting again from offset 0
+        Some(layout) => {
+            for filename in &config.files {
+                let mut hex = HexWriter::new(&mut out, layout);
+                encode_files(&config, slice::from_ref(filename), &mut state, &mut hex)?;
+                hex.finish()?;
+            }
+        }
+        None => encode_files(&config, &config.files, &mut state, &mut out)?,
+    }
+    Ok(())
+}
+
+fn show_pretty(config: &Config) -> MyResult<()> {
+    let width = terminal_size::terminal_size()
+        .map(|(width, _)| width.0 as usize)
+        .unwrap_or(80);
+    let printer = PrettyPrinter::new(width);
+    let mut pager = if config.pager {
+        pretty::spawn_pager()
+    } else {
+        None
+    };
+    let stdout = io::stdout();
+    let mut out: Box<dyn Write> = match pager.as_mut().and_then(|pager| pager.stdin.take()) {
+        Some(stdin) => Box::new(stdin),
+        None => Box::new(stdout.lock()),
+    };
+    for filename in &config.files {
+        match open(filename, config) {
             Err(err) => eprintln!("Failed to open {}: {}", filename, err),
-            Ok(buf) => {
-                let mut last_num = 0;
-                let mut previous_line_blank = false;
-                let line_ending = if config.show_ends {"$"} else {""};
-                for (line_number, each_line) in buf.lines().enumerate() {
-                    let each_line = each_line?;
-                    // skip consecutive blank lines with -s
-                    if config.squeeze_blank && each_line.is_empty() && previous_line_blank {
-                                continue;
-                    }
-                    if each_line.is_empty() {
-                        previous_line_blank = true;
-                    }else {
-                        previous_line_blank = false;
-                    }
-                    if config.number_lines {
-                        println!("{:>6}\t{}{}", line_number + 1, each_line, line_ending);
-                    }else if config.number_nonblank_lines {
-                        if !each_line.is_empty() {
-                            last_num += 1;
-                            println!("{:>6}\t{}{}", last_num, each_line, line_ending);
-                        }else {
-                            println!("{}", line_ending);
-                        }
-                    }else {
-                        println!("{}{}", each_line, line_ending);
-                    }
-                }
+            Ok(file) => match printer.print(filename, file, &mut out) {
+                // the pager was closed before the end
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => break,
+                result => result.map_err(|e| format!("{}: {}", filename, e))?,
             },
         }
     }
+    drop(out);
+    if let Some(mut pager) = pager {
+        pager.wait()?;
+    }
     Ok(())
 }
 
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+fn encode_files(
+    config: &Config,
+    files: &[String],
+    state: &mut LineState,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    match config.to_encoding {
+        Some(encoding) => {
+            let mut encoded = EncodeWriter::new(out, encoding, config.strict_encoding);
+            cat_files(config, files, state, &mut encoded)?;
+            encoded.finish()?;
+        }
+        None => cat_files(config, files, state, out)?,
+    }
+    Ok(())
+}
+
+fn cat_files(
+    config: &Config,
+    files: &[String],
+    state: &mut LineState,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let output = output_identity();
+    // with nothing done to the lines, an input is copied in blocks, so a
+    // long stretch without a newline is never held in memory, and with
+    // nothing to decode either, it is copied straight from the file
+    let whole = matches!(config.numbering, Never)
+        && !config.show_ends
+        && !config.show_tabs
+        && !config.show_nonprinting
+        && config.squeeze_blank.is_none()
+        && config.ranges.is_empty();
+    let plain = whole
+        && !config.hex_reverse
+        && config.from_encoding.is_none()
+        && config.to_encoding.is_none();
+    for filename in files {
+        if config.number_per_file {
+            *state = LineState::default();
+        }
+        if output.is_some() && file_identity(filename) == output {
+            eprintln!("{}: input file is output file", filename);
+            continue;
+        }
+        if plain {
+            match filename.as_str() {
+                "-" => copy_plain(filename, Ok(io::stdin().lock()), config.decompress, out),
+                _ => copy_plain(filename, File::open(filename), config.decompress, out),
+            }
+            .map_err(|e| format!("{}: {}", filename, e))?;
+            continue;
+        }
+        match open(filename, config) {
+            Err(err) => eprintln!("Failed to open {}: {}", filename, err),
+            Ok(mut file) if whole => {
+                io::copy(&mut file, out).map_err(|e| format!("{}: {}", filename, e))?;
+            }
+            Ok(file) => cat_file(config, state, file, out)
+                .map_err(|e| format!("{}: {}", filename, e))?,
+        }
+    }
+    Ok(())
+}
+
+// copies one input to the output, working on raw bytes so that invalid
+// UTF-8, CRLF endings and a missing final newline all come through untouched
+fn cat_file(
+    config: &Config,
+    state: &mut LineState,
+    mut file: impl BufRead,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let mut selection = if config.ranges.is_empty() {
+        None
+    } else {
+        Some(Selection::new(&config.ranges))
+    };
+    let mut line_number = 0;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if file.read_until(config.terminator, &mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+        match &mut selection {
+            None => cat_line(config, state, None, &line, out)?,
+            Some(selection) => {
+                if let Some((number, line)) = selection.push(line_number, mem::take(&mut line)) {
+                    cat_line(config, state, Some(number), &line, out)?;
+                }
+                if selection.is_done(line_number) {
+                    break;
+                }
+            }
+        }
+    }
+    if let Some(selection) = selection {
+        for (number, line) in selection.finish(line_number) {
+            cat_line(config, state, Some(number), &line, out)?;
+        }
+    }
+    Ok(())
+}
+
+// decorates and writes a single line; with --range the line keeps the number
+// it has in its file, otherwise it is numbered from the running count
+fn cat_line(
+    config: &Config,
+    state: &mut LineState,
+    line_number: Option<usize>,
+    line: &[u8],
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let (content, ending) = match line.split_last() {
+        Some((&last, content)) if last == config.terminator => (content, &line[line.len() - 1..]),
+        _ => (line, &b""[..]),
+    };
+    // a previous input that ended without a newline is continued here,
+    // so this line is neither numbered nor considered for squeezing
+    if state.at_line_start {
+        let is_blank = if config.whitespace_blank {
+            content.iter().all(u8::is_ascii_whitespace)
+        } else {
+            content.is_empty()
+        };
+        if is_blank {
+            // skip consecutive blank lines beyond what -s or --squeeze-max keep
+            if config.squeeze_blank.is_some_and(|max| state.blank_run >= max) {
+                return Ok(());
+            }
+            state.blank_run += 1;
+        } else {
+            state.blank_run = 0;
+        }
+        let numbered = match &config.numbering {
+            Never => false,
+            All => true,
+            NonBlank => !is_blank,
+            Matching(re) => re.is_match(content),
+        };
+        if numbered {
+            match line_number {
+                Some(line_number) => write_number(config, line_number as i64 - 1, out)?,
+                None => {
+                    write_number(config, state.line_number, out)?;
+                    state.line_number += 1;
+                }
+            }
+        }
+    } else {
+        state.blank_run = 0;
+    }
+    state.at_line_start = !ending.is_empty();
+    // like GNU cat, only mark lines that actually end in a newline, and
+    // make the carriage return of a CRLF ending visible as ^M
+    let show_end = config.show_ends && !ending.is_empty();
+    match content.strip_suffix(b"\r") {
+        Some(content) if show_end && config.terminator == b'\n' => {
+            write_content(config, content, out)?;
+            out.write_all(b"^M$")?;
+        }
+        _ => {
+            write_content(config, content, out)?;
+            if show_end {
+                out.write_all(b"$")?;
+            }
+        }
+    }
+    out.write_all(ending)?;
+    Ok(())
+}
+
+// picks the lines of one input that fall in any of the --range selections.
+// Positions counted from the end are only known once the input is exhausted,
+// so the last K lines (K being the largest such offset) are held back until
+// either K more lines have been read or the input ends.
+struct Selection<'a> {
+    ranges: &'a [LineRange],
+    held: VecDeque<(usize, Vec<u8>)>,
+    hold: usize,
+}
+
+impl<'a> Selection<'a> {
+    fn new(ranges: &'a [LineRange]) -> Self {
+        let hold = ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .filter(|&pos| pos < 0)
+            .map(|pos| pos.unsigned_abs() as usize)
+            .max()
+            .unwrap_or(0);
+        Selection {
+            ranges,
+            held: VecDeque::new(),
+            hold,
+        }
+    }
+
+    // takes the next line and returns the line that leaves the hold, if it
+    // was selected; a line with `hold` lines after it cannot be one of the
+    // last `hold` lines, so it lies before every position counted from the end
+    fn push(&mut self, line_number: usize, line: Vec<u8>) -> Option<(usize, Vec<u8>)> {
+        self.held.push_back((line_number, line));
+        if self.held.len() <= self.hold {
+            return None;
+        }
+        let (number, line) = self.held.pop_front()?;
+        let n = number as i64;
+        self.ranges
+            .iter()
+            .any(|range| range.start > 0 && range.start <= n && (range.end < 0 || n <= range.end))
+            .then_some((number, line))
+    }
+
+    // true once no later line can be selected, so reading can stop early
+    fn is_done(&self, line_number: usize) -> bool {
+        self.hold == 0
+            && self
+                .ranges
+                .iter()
+                .all(|range| range.end > 0 && line_number as i64 >= range.end)
+    }
+
+    // resolves the held lines now that the number of lines is known
+    fn finish(self, total: usize) -> Vec<(usize, Vec<u8>)> {
+        let total = total as i64;
+        let resolve = |pos: i64| if pos > 0 { pos } else { total + 1 + pos };
+        let ranges = self.ranges;
+        self.held
+            .into_iter()
+            .filter(|(number, _)| {
+                let n = *number as i64;
+                ranges
+                    .iter()
+                    .any(|range| resolve(range.start) <= n && n <= resolve(range.end))
+            })
+            .collect()
+    }
+}
+
+// writes the number of the nth numbered line followed by the separator
+fn write_number(config: &Config, nth: i64, out: &mut impl Write) -> MyResult<()> {
+    let value = nth
+        .checked_mul(config.number_increment)
+        .and_then(|step| step.checked_add(config.number_start))
+        .ok_or("line number overflow")?;
+    let width = config.number_width;
+    match config.number_format {
+        NumberFormat::Left => write!(out, "{:<width$}", value)?,
+        NumberFormat::Right => write!(out, "{:>width$}", value)?,
+        NumberFormat::RightZero => write!(out, "{:0width$}", value)?,
+    }
+    out.write_all(config.number_separator.as_bytes())?;
+    Ok(())
+}
+
+// writes the body of a line, rendering tabs and control or high-bit bytes
+// in caret and M- notation when -T or -v ask for it
+fn write_content(config: &Config, content: &[u8], out: &mut impl Write) -> MyResult<()> {
+    if !config.show_tabs && !config.show_nonprinting {
+        out.write_all(content)?;
+        return Ok(());
+    }
+    let mut shown = Vec::with_capacity(content.len());
+    for &byte in content {
+        match byte {
+            b'\t' if config.show_tabs => shown.extend_from_slice(b"^I"),
+            b'\t' | b'\n' => shown.push(byte),
+            _ if config.show_nonprinting => push_nonprinting(&mut shown, byte),
+            _ => shown.push(byte),
+        }
+    }
+    out.write_all(&shown)?;
+    Ok(())
+}
+
+// appends the GNU cat -v rendering of a single byte
+fn push_nonprinting(shown: &mut Vec<u8>, byte: u8) {
+    let byte = if byte >= 128 {
+        shown.extend_from_slice(b"M-");
+        byte - 128
+    } else {
+        byte
+    };
+    match byte {
+        0..=31 => shown.extend_from_slice(&[b'^', byte + 64]),
+        127 => shown.extend_from_slice(b"^?"),
+        _ => shown.push(byte),
+    }
+}
+
+// the device and inode of stdout, but only when it is a regular file: that
+// is the one case where reading an input can feed back into the output
+#[cfg(unix)]
+fn output_identity() -> Option<(u64, u64)> {
+    let stdout = io::stdout().as_fd().try_clone_to_owned().ok()?;
+    let metadata = File::from(stdout).metadata().ok()?;
+    metadata.is_file().then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn output_identity() -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(unix)]
+fn file_identity(filename: &str) -> Option<(u64, u64)> {
+    let metadata = match filename {
+        "-" => File::from(io::stdin().as_fd().try_clone_to_owned().ok()?).metadata(),
+        _ => fs::metadata(filename),
+    };
+    metadata.ok().map(|metadata| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_filename: &str) -> Option<(u64, u64)> {
+    None
+}
+
+// opens an input and peels off every layer the config asks for: compression
+// first, then a hex dump, then the character set
+fn open(filename: &str, config: &Config) -> MyResult<Box<dyn BufRead>> {
+    let mut file: Box<dyn Read> = match filename {
+        "-" => Box::new(io::stdin()),
+        _ => Box::new(File::open(filename)?),
+    };
+    let magic = peek(&mut file)?;
+    let format = compression(&magic);
+    let file = BufReader::with_capacity(BUF_SIZE, io::Cursor::new(magic).chain(file));
+    let mut file = match format {
+        Some(format) if config.decompress => decoder(file, format)?,
+        _ => Box::new(file),
+    };
+    if config.hex_reverse {
+        file = Box::new(BufReader::new(HexReader::new(file)));
+    }
+    if let Some(encoding) = config.from_encoding {
+        file = Box::new(BufReader::new(DecodeReader::new(
+            file,
+            encoding,
+            config.strict_encoding,
+        )));
+    }
+    Ok(file)
+}
+
+// copies one input to the output without looking at its contents; with a
+// File or stdin on one end and stdout on the other, io::copy lets the kernel
+// move the data on Linux (copy_file_range, splice or sendfile) and falls back
+// to copying through a buffer everywhere else. As with `open`, an input
+// that cannot be opened or peeked at is reported and skipped.
+fn copy_plain<R: Read>(
+    filename: &str,
+    file: io::Result<R>,
+    decompress: bool,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let peeked = file.and_then(|mut file| Ok((peek(&mut file)?, file)));
+    let (magic, mut file) = match peeked {
+        Err(err) => {
+            eprintln!("Failed to open {}: {}", filename, err);
+            return Ok(());
+        }
+        Ok(peeked) => peeked,
+    };
+    match compression(&magic) {
+        Some(format) if decompress => {
+            let file = BufReader::with_capacity(BUF_SIZE, io::Cursor::new(magic).chain(file));
+            match decoder(file, format) {
+                Err(err) => eprintln!("Failed to open {}: {}", filename, err),
+                Ok(mut file) => {
+                    io::copy(&mut file, out)?;
+                }
+            }
+        }
+        _ => {
+            out.write_all(&magic)?;
+            io::copy(&mut file, out)?;
+        }
+    }
+    Ok(())
+}
+
+// the longest magic number `compression` looks for
+const MAGIC_LEN: usize = 6;
+
+// reads the first bytes of an input, which a pipe may hand over a few at a
+// time, until there are enough to recognise any compression or it ends
+fn peek(file: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    file.take(MAGIC_LEN as u64).read_to_end(&mut magic)?;
+    Ok(magic)
+}
+
+#[derive(Debug, PartialEq)]
+enum Compression {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+// recognises compressed input by its magic bytes rather than its name, so
+// stdin and misnamed files are handled too
+fn compression(magic: &[u8]) -> Option<Compression> {
+    match magic {
+        [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+        [b'B', b'Z', b'h', ..] => Some(Compression::Bzip2),
+        [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Compression::Xz),
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+        _ => None,
     }
 }
 
+// concatenated members and frames are all decoded, just like zcat
+fn decoder<'a>(
+    file: impl BufRead + 'a,
+    format: Compression,
+) -> MyResult<Box<dyn BufRead + 'a>> {
+    Ok(match format {
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+        Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(file))),
+        Compression::Xz => Box::new(BufReader::new(XzDecoder::new_multi_decoder(file))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(file)?)),
+    })
+}
+
 pub fn get_args() -> MyResult<Config> {
     let matches = App::new("catr")
         .version("0.1.0")
@@ -86,6 +603,137 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Number non-blank lines")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("number_style")
+                .long("number-style")
+                .value_name("STYLE")
+                .help("Number lines by STYLE: a (all), t (non-empty),\nn (none) or pREGEX (lines matching REGEX)")
+                .conflicts_with_all(&["number", "number_nonblank"]),
+        )
+        .arg(
+            Arg::with_name("number_width")
+                .long("number-width")
+                .value_name("WIDTH")
+                .help("Use WIDTH columns for line numbers")
+                .default_value("6"),
+        )
+        .arg(
+            Arg::with_name("number_separator")
+                .long("number-separator")
+                .value_name("STRING")
+                .help("Add STRING after each line number")
+                .default_value("\t")
+                .hide_default_value(true),
+        )
+        .arg(
+            Arg::with_name("number_start")
+                .long("number-start")
+                .value_name("NUMBER")
+                .help("First line number")
+                .allow_hyphen_values(true)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name("number_increment")
+                .long("number-increment")
+                .value_name("NUMBER")
+                .help("Line number increment")
+                .allow_hyphen_values(true)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name("number_format")
+                .long("number-format")
+                .value_name("FORMAT")
+                .help("Justify line numbers by FORMAT: ln (left),\nrn (right) or rz (right, zero-padded)")
+                .possible_values(&["ln", "rn", "rz"])
+                .default_value("rn"),
+        )
+        .arg(
+            Arg::with_name("no_decompress")
+                .long("no-decompress")
+                .help("Copy gzip, bzip2, xz and zstd input as is")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("range")
+                .long("range")
+                .value_name("START:END")
+                .help("Only show lines START to END of each file,\ncounting from 1; either may be left out, and\nnegative positions count back from the last line")
+                .multiple(true)
+                .number_of_values(1)
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("hex")
+                .long("hex")
+                .help("Show the output as an xxd-style hex dump")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("hex_cols")
+                .long("hex-cols")
+                .value_name("COLS")
+                .help("Bytes per line of the hex dump, at most 256")
+                .default_value("16"),
+        )
+        .arg(
+            Arg::with_name("hex_group")
+                .long("hex-group")
+                .value_name("BYTES")
+                .help("Bytes per group of the hex dump, 0 for none")
+                .default_value("2"),
+        )
+        .arg(
+            Arg::with_name("hex_reverse")
+                .long("hex-reverse")
+                .help("Read hex dumps and turn them back into bytes")
+                .takes_value(false)
+                .conflicts_with("hex"),
+        )
+        .arg(
+            Arg::with_name("from_encoding")
+                .long("from-encoding")
+                .value_name("ENCODING")
+                .help("Decode input from ENCODING into UTF-8; auto\nfollows a byte order mark and assumes UTF-8\nwithout one"),
+        )
+        .arg(
+            Arg::with_name("to_encoding")
+                .long("to-encoding")
+                .value_name("ENCODING")
+                .help("Encode output from UTF-8 into ENCODING"),
+        )
+        .arg(
+            Arg::with_name("strict_encoding")
+                .long("strict-encoding")
+                .help("Fail on bytes or characters that cannot be\nconverted instead of replacing them")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .short("z")
+                .long("zero-terminated")
+                .help("Records end with NUL, not newline")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("pretty")
+                .long("pretty")
+                .help("On a terminal, show a header, line numbers and\nsyntax highlighting, wrapping long lines")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("no_pager")
+                .long("no-pager")
+                .help("With --pretty, do not page the output")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("number_per_file")
+                .long("number-per-file")
+                .help("Restart line numbers at 1 for each file")
+                .takes_value(false),
+        )
         .arg(
             Arg::with_name("show_ends")
                 .short("E")
@@ -93,6 +741,39 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Display $ at end of each line")
                 .takes_value(false)
         )
+        .arg(
+            Arg::with_name("show_tabs")
+                .short("T")
+                .long("show-tabs")
+                .help("Display TAB characters as ^I")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("show_nonprinting")
+                .short("v")
+                .long("show-nonprinting")
+                .help("Use ^ and M- notation, except for LFD and TAB")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("show_all")
+                .short("A")
+                .long("show-all")
+                .help("Equivalent to -vET")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("e")
+                .short("e")
+                .help("Equivalent to -vE")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("t")
+                .short("t")
+                .help("Equivalent to -vT")
+                .takes_value(false)
+        )
         .arg(
             Arg::with_name("squeeze_blank")
                 .short("s")
@@ -100,13 +781,190 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Suppress repeated empty output lines")
                 .takes_value(false)
         )
+        .arg(
+            Arg::with_name("squeeze_max")
+                .long("squeeze-max")
+                .value_name("N")
+                .help("Keep at most N consecutive empty output lines")
+                .conflicts_with("squeeze_blank"),
+        )
+        .arg(
+            Arg::with_name("whitespace_blank")
+                .long("whitespace-blank")
+                .help("Treat lines of nothing but whitespace as empty\nfor -b, -s and --squeeze-max")
+                .takes_value(false),
+        )
         .get_matches();
 
+    let numbering = match matches.value_of("number_style") {
+        Some(style) => parse_number_style(style)
+            .map_err(|e| format!("illegal number style -- {}", e))?,
+        None if matches.is_present("number") => All,
+        None if matches.is_present("number_nonblank") => NonBlank,
+        None => Never,
+    };
+
+    // the formatter takes widths of up to u16::MAX
+    let number_width = matches
+        .value_of("number_width")
+        .map(|val| parse_bounded_int(val, u16::MAX as usize))
+        .transpose()
+        .map_err(|e| format!("illegal number width -- {}", e))?;
+
+    let number_start = matches
+        .value_of("number_start")
+        .map(parse_int)
+        .transpose()
+        .map_err(|e| format!("illegal starting line number -- {}", e))?;
+
+    let number_increment = matches
+        .value_of("number_increment")
+        .map(parse_int)
+        .transpose()
+        .map_err(|e| format!("illegal line number increment -- {}", e))?;
+
+    let ranges = matches
+        .values_of("range")
+        .map(|ranges| ranges.map(parse_range).collect::<MyResult<Vec<_>>>())
+        .transpose()?
+        .unwrap_or_default();
+
+    // no more than xxd allows
+    let hex_cols = matches
+        .value_of("hex_cols")
+        .map(|val| parse_bounded_int(val, 256))
+        .transpose()
+        .map_err(|e| format!("illegal hex column count -- {}", e))?;
+
+    let hex_group = matches
+        .value_of("hex_group")
+        .map(|val| val.parse().map_err(|_| val))
+        .transpose()
+        .map_err(|e| format!("illegal hex group size -- {}", e))?;
+
+    let hex = if matches.is_present("hex") {
+        Some(HexLayout {
+            cols: hex_cols.unwrap(),
+            group: hex_group.unwrap(),
+        })
+    } else {
+        None
+    };
+
+    let from_encoding = matches
+        .value_of("from_encoding")
+        .map(|label| encoding::input_encoding(label).ok_or(label))
+        .transpose()
+        .map_err(|e| format!("unknown encoding -- {}", e))?;
+
+    let to_encoding = matches
+        .value_of("to_encoding")
+        .map(|label| encoding::output_encoding(label).ok_or(label))
+        .transpose()
+        .map_err(|e| format!("unknown encoding -- {}", e))?;
+
+    let number_format = match matches.value_of("number_format") {
+        Some("ln") => NumberFormat::Left,
+        Some("rz") => NumberFormat::RightZero,
+        _ => NumberFormat::Right,
+    };
+
+    let squeeze_max = matches
+        .value_of("squeeze_max")
+        .map(|val| val.parse().map_err(|_| val))
+        .transpose()
+        .map_err(|e| format!("illegal blank line count -- {}", e))?;
+
+    let squeeze_blank = match squeeze_max {
+        None if matches.is_present("squeeze_blank") => Some(1),
+        squeeze_max => squeeze_max,
+    };
+
+    let show_all = matches.is_present("show_all");
+
     Ok(Config {
         files: matches.values_of_lossy("files").unwrap(),
-        number_lines: matches.is_present("number"),
-        number_nonblank_lines: matches.is_present("number_nonblank"),
-        show_ends: matches.is_present("show_ends"),
-        squeeze_blank: matches.is_present("squeeze_blank"),
+        numbering,
+        number_width: number_width.unwrap(),
+        number_separator: matches.value_of_lossy("number_separator").unwrap().into(),
+        number_start: number_start.unwrap(),
+        number_increment: number_increment.unwrap(),
+        number_format,
+        show_ends: show_all || matches.is_present("show_ends") || matches.is_present("e"),
+        show_tabs: show_all || matches.is_present("show_tabs") || matches.is_present("t"),
+        show_nonprinting: show_all
+            || matches.is_present("show_nonprinting")
+            || matches.is_present("e")
+            || matches.is_present("t"),
+        squeeze_blank,
+        whitespace_blank: matches.is_present("whitespace_blank"),
+        number_per_file: matches.is_present("number_per_file"),
+        decompress: !matches.is_present("no_decompress"),
+        ranges,
+        terminator: if matches.is_present("zero_terminated") { b'\0' } else { b'\n' },
+        hex,
+        hex_reverse: matches.is_present("hex_reverse"),
+        from_encoding,
+        to_encoding,
+        strict_encoding: matches.is_present("strict_encoding"),
+        pretty: matches.is_present("pretty"),
+        pager: !matches.is_present("no_pager"),
     })
 }
+
+fn parse_positive_int(val: &str) -> MyResult<usize> {
+    match val.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(From::from(val)),
+    }
+}
+
+// a positive integer no larger than `max`
+fn parse_bounded_int(val: &str, max: usize) -> MyResult<usize> {
+    match parse_positive_int(val) {
+        Ok(n) if n <= max => Ok(n),
+        _ => Err(From::from(val)),
+    }
+}
+
+fn parse_int(val: &str) -> MyResult<i64> {
+    val.parse().map_err(|_| From::from(val))
+}
+
+fn parse_number_style(val: &str) -> MyResult<Numbering> {
+    match val {
+        "a" => Ok(All),
+        "t" => Ok(NonBlank),
+        "n" => Ok(Never),
+        _ => match val.strip_prefix('p') {
+            Some(pattern) => Regex::new(pattern)
+                .map(Matching)
+                .map_err(|_| From::from(val)),
+            None => Err(From::from(val)),
+        },
+    }
+}
+
+fn parse_range(val: &str) -> MyResult<LineRange> {
+    let illegal = || format!("illegal line range -- {}", val);
+    let position = |pos: &str, default: i64| -> MyResult<i64> {
+        match pos {
+            "" => Ok(default),
+            _ => match pos.parse() {
+                Ok(n) if n != 0 => Ok(n),
+                _ => Err(From::from(illegal())),
+            },
+        }
+    };
+    let (start, end) = match val.split_once(':') {
+        Some((start, end)) => (position(start, 1)?, position(end, -1)?),
+        None => {
+            let pos = position(val, 0)?;
+            (pos, pos)
+        }
+    };
+    if start == 0 || (start.signum() == end.signum() && start > end) {
+        return Err(From::from(illegal()));
+    }
+    Ok(LineRange { start, end })
+}
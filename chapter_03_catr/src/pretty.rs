@@ -0,0 +1,175 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use unicode_width::UnicodeWidthChar;
+
+const NUMBER_WIDTH: usize = 6;
+const GUTTER_WIDTH: usize = NUMBER_WIDTH + 3;
+const TAB_WIDTH: usize = 4;
+const THEME: &str = "base16-ocean.dark";
+
+// renders files for a terminal: a header with the file name, a line number
+// gutter and syntax highlighting, with long lines wrapped to the width
+pub struct PrettyPrinter {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+    width: usize,
+}
+
+impl PrettyPrinter {
+    pub fn new(width: usize) -> Self {
+        let mut themes = ThemeSet::load_defaults();
+        PrettyPrinter {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            theme: themes.themes.remove(THEME).unwrap_or_default(),
+            width: width.max(GUTTER_WIDTH + 10),
+        }
+    }
+
+    pub fn print(
+        &self,
+        filename: &str,
+        mut file: impl BufRead,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let mut line = Vec::new();
+        file.read_until(b'\n', &mut line)?;
+        let syntax = self.syntax_for(filename, &String::from_utf8_lossy(&line));
+        let mut highlighter = HighlightLines::new(syntax, &self.theme);
+
+        self.rule('┬', out)?;
+        writeln!(out, "{:NUMBER_WIDTH$} │ File: {}", "", filename)?;
+        self.rule('┼', out)?;
+        let mut line_number = 0;
+        while !line.is_empty() {
+            line_number += 1;
+            let text = String::from_utf8_lossy(&line);
+            let ranges = highlighter
+                .highlight_line(&text, &self.syntaxes)
+                .map_err(io::Error::other)?;
+            self.print_line(line_number, &ranges, out)?;
+            line.clear();
+            file.read_until(b'\n', &mut line)?;
+        }
+        self.rule('┴', out)
+    }
+
+    // the extension (or the whole name, for files like Makefile) decides the
+    // language, then a shebang or mode line, and plain text is the fallback
+    fn syntax_for(&self, filename: &str, first_line: &str) -> &SyntaxReference {
+        let path = Path::new(filename);
+        path.extension()
+            .or_else(|| path.file_name())
+            .and_then(|name| name.to_str())
+            .and_then(|name| self.syntaxes.find_syntax_by_extension(name))
+            .or_else(|| self.syntaxes.find_syntax_by_first_line(first_line))
+            .unwrap_or_else(|| self.syntaxes.find_syntax_plain_text())
+    }
+
+    fn rule(&self, joint: char, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{}{}{}",
+            "─".repeat(GUTTER_WIDTH - 2),
+            joint,
+            "─".repeat(self.width - GUTTER_WIDTH + 1)
+        )
+    }
+
+    fn print_line(
+        &self,
+        line_number: usize,
+        ranges: &[(Style, &str)],
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let text_width = self.width - GUTTER_WIDTH;
+        let mut shown = format!("{:>NUMBER_WIDTH$} │ ", line_number);
+        let mut column = 0;
+        for (style, text) in ranges {
+            let color = style.foreground;
+            let escape = format!("\x1b[38;2;{};{};{}m", color.r, color.g, color.b);
+            shown.push_str(&escape);
+            for c in text.chars() {
+                let width = match c {
+                    '\n' | '\r' => continue,
+                    '\t' => TAB_WIDTH - column % TAB_WIDTH,
+                    _ => c.width().unwrap_or(0),
+                };
+                if column + width > text_width {
+                    shown.push_str("\x1b[0m\n");
+                    shown.push_str(&format!("{:NUMBER_WIDTH$} │ ", ""));
+                    shown.push_str(&escape);
+                    column = 0;
+                }
+                match c {
+                    '\t' => shown.push_str(&" ".repeat(width)),
+                    _ => shown.push(c),
+                }
+                column += width;
+            }
+        }
+        shown.push_str("\x1b[0m\n");
+        out.write_all(shown.as_bytes())
+    }
+}
+
+// starts $PAGER, or less when that is not set, to show the output a screen
+// at a time; None means the output should go straight to the terminal
+pub fn spawn_pager() -> Option<Child> {
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less -RFX".to_string());
+    let mut words = pager.split_whitespace();
+    let program = words.next()?;
+    Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()
+        .ok()
+}
+
+// --------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::PrettyPrinter;
+    use std::io::Cursor;
+
+    fn strip_escapes(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn test_print() {
+        let printer = PrettyPrinter::new(30);
+        let mut out = Vec::new();
+        let text = "fn main() {\n\tprintln!(\"a long line\");\n}\n";
+        printer.print("main.rs", Cursor::new(text), &mut out).unwrap();
+        let shown = strip_escapes(&String::from_utf8(out).unwrap());
+        let lines: Vec<_> = shown.lines().collect();
+        assert_eq!(lines[1], "       │ File: main.rs");
+        assert_eq!(lines[3], "     1 │ fn main() {");
+        assert_eq!(lines[4], "     2 │     println!(\"a long ");
+        assert_eq!(lines[5], "       │ line\");");
+        assert_eq!(lines[6], "     3 │ }");
+        assert_eq!(lines.len(), 8);
+    }
+
+    #[test]
+    fn test_syntax_for() {
+        let printer = PrettyPrinter::new(80);
+        assert_eq!(printer.syntax_for("lib.rs", "").name, "Rust");
+        assert_eq!(printer.syntax_for("script", "#!/usr/bin/env python3\n").name, "Python");
+        assert_eq!(printer.syntax_for("-", "hello\n").name, "Plain Text");
+    }
+}
@@ -2,7 +2,11 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
 use std::error::Error;
-use std::fs;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::{self, Stdio};
+use std::thread;
+use std::time::Duration;
 
 type TestResult = Result<(), Box<dyn Error>>;
 
@@ -11,6 +15,16 @@ const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const SPIDERS: &str = "tests/inputs/spiders.txt";
 const BUSTLE: &str = "tests/inputs/the-bustle.txt";
+const LATIN1: &str = "tests/inputs/latin1.bin";
+const CONTROL: &str = "tests/inputs/control.bin";
+const GZ: &str = "tests/inputs/fox-bustle.txt.gz";
+const BZ2: &str = "tests/inputs/fox-bustle.txt.bz2";
+const XZ: &str = "tests/inputs/fox-bustle.txt.xz";
+const ZST: &str = "tests/inputs/fox-bustle.txt.zst";
+const BUSTLE16: &str = "tests/inputs/the-bustle.utf16le.txt";
+const CREME: &str = "tests/inputs/creme-brulee.latin1.txt";
+const RECORDS: &str = "tests/inputs/records.bin";
+const BLANKS: &str = "tests/inputs/blanks.txt";
 
 // --------------------------------------------------
 #[test]
@@ -52,9 +66,125 @@ fn skips_bad_file() -> TestResult {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_line_number_overflow() -> TestResult {
+    for args in [
+        &["--number-increment", "9223372036854775807"][..],
+        &["--number-start", "9223372036854775807"],
+        &["--number-start=-9223372036854775808", "--number-increment=-1"],
+    ] {
+        Command::cargo_bin(PRG)?
+            .arg("-n")
+            .args(args)
+            .arg(BUSTLE)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("line number overflow"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_unreadable_file() -> TestResult {
+    // the plain path and the line by line one alike
+    for (args, expected_file) in [
+        (&["tests/inputs", FOX][..], "tests/expected/fox.txt.out"),
+        (&["-n", "tests/inputs", FOX], "tests/expected/fox.txt.n.out"),
+    ] {
+        let expected = fs::read_to_string(expected_file)?;
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .assert()
+            .success()
+            .stderr(predicate::str::is_match(
+                "Failed to open tests/inputs: .* [(]os error 21[)]",
+            )?)
+            .stdout(expected);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_plain() -> TestResult {
+    for input in [CONTROL, LATIN1, RECORDS] {
+        run(&[input], input)?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_number_width() -> TestResult {
+    for bad in ["0", "65536"] {
+        Command::cargo_bin(PRG)?
+            .args(["-n", "--number-width", bad, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!("illegal number width -- {}", bad)));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_number_style() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--number-style", "p(", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal number style -- p("));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_input_that_is_output() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let out_path = dir.path().join("out.txt");
+    fs::copy(FOX, &out_path)?;
+    let out_file = OpenOptions::new().append(true).open(&out_path)?;
+    let output = process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+        .args([FOX, out_path.to_str().unwrap()])
+        .stdout(out_file)
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)?
+        .contains("out.txt: input file is output file"));
+    let fox = fs::read_to_string(FOX)?;
+    assert_eq!(fs::read_to_string(&out_path)?, fox.repeat(2));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_range() -> TestResult {
+    for bad in &["0:3", "7:2", "-2:-5", "x", "1:y"] {
+        Command::cargo_bin(PRG)?
+            .args(["--range", bad, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!("illegal line range -- {}", bad)));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_squeeze_max() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--squeeze-max", "many", BLANKS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal blank line count -- many"));
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> TestResult {
-    let expected = fs::read_to_string(expected_file)?;
+    let expected = fs::read(expected_file)?;
     Command::cargo_bin(PRG)?
         .args(args)
         .assert()
@@ -69,8 +199,8 @@ fn run_stdin(
     args: &[&str],
     expected_file: &str,
 ) -> TestResult {
-    let input = fs::read_to_string(input_file)?;
-    let expected = fs::read_to_string(expected_file)?;
+    let input = fs::read(input_file)?;
+    let expected = fs::read(expected_file)?;
     Command::cargo_bin(PRG)?
         .args(args)
         .write_stdin(input)
@@ -181,6 +311,47 @@ fn bustle_b() -> TestResult {
     run(&["-b", BUSTLE], "tests/expected/the-bustle.txt.b.out")
 }
 
+// --------------------------------------------------
+#[test]
+fn bustle_nl_rz() -> TestResult {
+    run(
+        &[
+            "--number-style",
+            "a",
+            "--number-width",
+            "3",
+            "--number-separator",
+            ": ",
+            "--number-start",
+            "5",
+            "--number-increment",
+            "2",
+            "--number-format",
+            "rz",
+            BUSTLE,
+        ],
+        "tests/expected/the-bustle.txt.nl-rz.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_nl_ln() -> TestResult {
+    run(
+        &["-n", "--number-format", "ln", "--number-start=-3", BUSTLE],
+        "tests/expected/the-bustle.txt.nl-ln.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_nl_pattern() -> TestResult {
+    run(
+        &["--number-style", "p^The", BUSTLE],
+        "tests/expected/the-bustle.txt.nl-p.out",
+    )
+}
+
 // --------------------------------------------------
 #[test]
 fn all() -> TestResult {
@@ -198,3 +369,455 @@ fn all_n() -> TestResult {
 fn all_b() -> TestResult {
     run(&[FOX, SPIDERS, BUSTLE, "-b"], "tests/expected/all.b.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn all_n_per_file() -> TestResult {
+    run(
+        &[FOX, SPIDERS, BUSTLE, "-n", "--number-per-file"],
+        "tests/expected/all.n.per-file.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all_b_per_file() -> TestResult {
+    run(
+        &[FOX, SPIDERS, BUSTLE, "-b", "--number-per-file"],
+        "tests/expected/all.b.per-file.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1() -> TestResult {
+    run(&[LATIN1], "tests/expected/latin1.bin.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_n() -> TestResult {
+    run(&["-n", LATIN1], "tests/expected/latin1.bin.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_b() -> TestResult {
+    run(&["-b", LATIN1], "tests/expected/latin1.bin.b.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_e() -> TestResult {
+    run(&["-E", LATIN1], "tests/expected/latin1.bin.E.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_s() -> TestResult {
+    run(&["-s", LATIN1], "tests/expected/latin1.bin.s.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_ns() -> TestResult {
+    run(&["-n", "-s", LATIN1], "tests/expected/latin1.bin.ns.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_be() -> TestResult {
+    run(&["-b", "-E", LATIN1], "tests/expected/latin1.bin.bE.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_stdin_s() -> TestResult {
+    run_stdin(LATIN1, &["-s"], "tests/expected/latin1.bin.s.stdin.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_v() -> TestResult {
+    run(&["--show-nonprinting", CONTROL], "tests/expected/control.bin.v.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_show_tabs() -> TestResult {
+    run(&["--show-tabs", CONTROL], "tests/expected/control.bin.T.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_a() -> TestResult {
+    run(&["--show-all", CONTROL], "tests/expected/control.bin.A.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_e() -> TestResult {
+    run(&["-e", CONTROL], "tests/expected/control.bin.e.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_t() -> TestResult {
+    run(&["-t", CONTROL], "tests/expected/control.bin.t.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_ve() -> TestResult {
+    run(&["-vE", CONTROL], "tests/expected/control.bin.vE.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn latin1_fox_n() -> TestResult {
+    run(&["-n", LATIN1, FOX], "tests/expected/latin1.bin.fox.txt.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn gz_n() -> TestResult {
+    run(&["-n", GZ], "tests/expected/fox-bustle.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn bz2_n() -> TestResult {
+    run(&["-n", BZ2], "tests/expected/fox-bustle.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn xz_n() -> TestResult {
+    run(&["-n", XZ], "tests/expected/fox-bustle.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn zst_n() -> TestResult {
+    run(&["-n", ZST], "tests/expected/fox-bustle.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn xz_stdin_n() -> TestResult {
+    run_stdin(XZ, &["-n"], "tests/expected/fox-bustle.n.out")
+}
+
+// --------------------------------------------------
+// a pipe may hand over the magic number in pieces, which must be put
+// together before telling whether the input is compressed
+#[test]
+fn xz_split_magic_stdin() -> TestResult {
+    let xz = fs::read(XZ)?;
+    for (args, expected_file) in [
+        (&[][..], "tests/expected/fox-bustle.out"),
+        (&["-n"], "tests/expected/fox-bustle.n.out"),
+    ] {
+        let mut child = process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(&xz[..3])?;
+        stdin.flush()?;
+        thread::sleep(Duration::from_millis(300));
+        stdin.write_all(&xz[3..])?;
+        drop(stdin);
+        let output = child.wait_with_output()?;
+        assert!(output.status.success());
+        assert_eq!(output.stdout, fs::read(expected_file)?);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn gz_no_decompress() -> TestResult {
+    run(&["--no-decompress", GZ], GZ)
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_range_n() -> TestResult {
+    run(
+        &["-n", "--range", "2:4", BUSTLE],
+        "tests/expected/the-bustle.txt.range2-4.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_range_from_end() -> TestResult {
+    run(
+        &["-n", "--range=-3:", BUSTLE],
+        "tests/expected/the-bustle.txt.range-3.n.out",
+    )
+}
+
+// --------------------------------------------------
+// only the lines read are held, however far from the end a range starts
+#[test]
+fn bustle_range_far_from_end() -> TestResult {
+    for range in ["--range=-1000000000:", "--range=-9223372036854775807:"] {
+        run(&[range, BUSTLE], "tests/expected/the-bustle.txt.out")?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_range_stdin() -> TestResult {
+    run_stdin(
+        BUSTLE,
+        &["--range=:-7", "--range", "-4:-2"],
+        "tests/expected/the-bustle.txt.range-mixed.stdin.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all_ranges_n() -> TestResult {
+    run(
+        &["-n", "--range", "1:2", "--range=-1:", "--range", "2:3", BUSTLE, SPIDERS],
+        "tests/expected/all.ranges.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn control_hex() -> TestResult {
+    run(&["--hex", CONTROL], "tests/expected/control.bin.hex.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn control_hex_layout() -> TestResult {
+    run(
+        &["--hex", "--hex-cols", "5", "--hex-group", "3", CONTROL],
+        "tests/expected/control.bin.hex-c5-g3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn all_hex_stdin() -> TestResult {
+    run_stdin(
+        LATIN1,
+        &["--hex", "--hex-group", "0", FOX, "-"],
+        "tests/expected/fox-latin1.hex-g0.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn control_hex_reverse() -> TestResult {
+    run_stdin(
+        "tests/expected/control.bin.hex-c5-g3.out",
+        &["--hex-reverse"],
+        CONTROL,
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn hex_reverse_offset_gap() -> TestResult {
+    let mut expected = vec![b'A'];
+    expected.resize(0x10_0000, 0);
+    expected.push(b'B');
+    Command::cargo_bin(PRG)?
+        .arg("--hex-reverse")
+        .write_stdin("00000000: 41\n00100000: 42\n")
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_hex_cols() -> TestResult {
+    for bad in ["0", "257", "18446744073709551615"] {
+        Command::cargo_bin(PRG)?
+            .args(["--hex", "--hex-cols", bad, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!("illegal hex column count -- {}", bad)));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn all_hex_per_file() -> TestResult {
+    run(
+        &["--hex", "--hex-cols", "5", FOX, CONTROL],
+        "tests/expected/fox-control.hex-c5.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_hex_dump() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .arg("--hex-reverse")
+        .write_stdin("00000000: 6g\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid hex dump line: 00000000: 6g"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle16_auto() -> TestResult {
+    run(
+        &["-n", "-E", "--from-encoding", "auto", BUSTLE16],
+        "tests/expected/the-bustle.utf16le.txt.nE.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn creme_latin1() -> TestResult {
+    run(
+        &["-n", "--from-encoding", "latin1", CREME],
+        "tests/expected/creme-brulee.latin1.txt.n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_to_utf16be() -> TestResult {
+    run(
+        &["--to-encoding", "utf-16be", BUSTLE],
+        "tests/expected/the-bustle.txt.utf16be.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_strict_malformed() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--from-encoding", "utf-8", "--strict-encoding", CREME])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("malformed UTF-8 input"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_strict_unmappable() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--to-encoding", "latin2", "--strict-encoding", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot encode '—' as ISO-8859-2"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_unknown_encoding() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--from-encoding", "klingon", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown encoding -- klingon"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn records_zn() -> TestResult {
+    run(&["-z", "-n", RECORDS], "tests/expected/records.bin.zn.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn records_zb() -> TestResult {
+    run(&["-z", "-b", RECORDS], "tests/expected/records.bin.zb.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn records_zs() -> TestResult {
+    run(&["-z", "-s", RECORDS], "tests/expected/records.bin.zs.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn records_zns() -> TestResult {
+    run(&["-z", "-ns", RECORDS], "tests/expected/records.bin.zns.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn records_zbse() -> TestResult {
+    run(&["-z", "-bsE", RECORDS], "tests/expected/records.bin.zbsE.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn records_stdin_zn() -> TestResult {
+    run_stdin(
+        RECORDS,
+        &["--zero-terminated", "--number"],
+        "tests/expected/records.bin.zn.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn pretty_piped_is_plain() -> TestResult {
+    run(&["--pretty", "-n", BUSTLE], "tests/expected/the-bustle.txt.n.out")?;
+    run(&["--pretty", LATIN1], "tests/expected/latin1.bin.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn blanks_s() -> TestResult {
+    run(&["-s", BLANKS], "tests/expected/blanks.txt.s.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn blanks_squeeze_max() -> TestResult {
+    run(
+        &["--squeeze-max", "2", BLANKS],
+        "tests/expected/blanks.txt.squeeze2.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn blanks_squeeze_max_zero() -> TestResult {
+    run(
+        &["--squeeze-max", "0", BLANKS],
+        "tests/expected/blanks.txt.squeeze0.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn blanks_bs_whitespace() -> TestResult {
+    run(
+        &["-bs", "--whitespace-blank", BLANKS],
+        "tests/expected/blanks.txt.bs.ws.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn blanks_n_squeeze_max_whitespace() -> TestResult {
+    run(
+        &["-n", "--squeeze-max", "2", "--whitespace-blank", BLANKS],
+        "tests/expected/blanks.txt.n.squeeze2.ws.out",
+    )
+}
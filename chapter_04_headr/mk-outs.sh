@@ -13,8 +13,46 @@ for FILE in $INPUTS/*.txt; do
     head -c 1 $FILE > ${OUT_DIR}/${BASENAME}.c1.out
     head -c 2 $FILE > ${OUT_DIR}/${BASENAME}.c2.out
     head -c 4 $FILE > ${OUT_DIR}/${BASENAME}.c4.out
+    head -n -2 $FILE > ${OUT_DIR}/${BASENAME}.n-2.out
+    head -c -3 $FILE > ${OUT_DIR}/${BASENAME}.c-3.out
 done
 
+for NUM in 1 260 1000 1b -1b 1K; do
+    head -c $NUM $INPUTS/binary.bin > $OUT_DIR/binary.bin.c$NUM.out
+done
+
+head -n 1K $INPUTS/ten.txt > $OUT_DIR/ten.txt.n1K.out
+
+head -v $INPUTS/one.txt > $OUT_DIR/one.txt.v.out
+head -z -n 2 $INPUTS/records.bin > $OUT_DIR/records.bin.z.n2.out
+head -z -n -2 $INPUTS/records.bin > $OUT_DIR/records.bin.z.n-2.out
+
+SECTIONS=$INPUTS/sections.md
+sed '/^$/,$d' $SECTIONS > $OUT_DIR/sections.md.until-blank.out
+sed '/^$/q' $SECTIONS > $OUT_DIR/sections.md.until-blank-inclusive.out
+sed '/^---$/,$d' $SECTIONS | head -n 2 > $OUT_DIR/sections.md.until-rule.n2.out
+sed '/^---$/,$d' $SECTIONS | head -n -1 > $OUT_DIR/sections.md.until-rule.n-1.out
+(
+    echo "==> $SECTIONS <=="
+    sed '/^$/,$d' $SECTIONS | head -n 3
+    echo
+    echo "==> $INPUTS/ten.txt <=="
+    sed '/^$/,$d' $INPUTS/ten.txt | head -n 3
+) > $OUT_DIR/sections.ten.until-blank.n3.out
+
+# GNU head cannot count characters, so these are written out by hand
+printf '\xc3\x96' > $OUT_DIR/one.txt.m1.out
+printf 'e\xcc\x81\xf0\x9f\x87\xab' > $OUT_DIR/graphemes.bin.m3.out
+printf 'e\xcc\x81\xf0\x9f\x87\xab\xf0\x9f\x87\xb7\xf0\x9f\x91\x8d\xf0\x9f\x8f\xbdx\xff' \
+    > $OUT_DIR/graphemes.bin.m8.out
+printf 'e\xcc\x81\xf0\x9f\x87\xab\xf0\x9f\x87\xb7' > $OUT_DIR/graphemes.bin.graphemes.m2.out
+cp $OUT_DIR/graphemes.bin.m8.out $OUT_DIR/graphemes.bin.graphemes.m5.out
+
+tail -n +4 $INPUTS/ten.txt | head -n 2 > $OUT_DIR/ten.txt.skip-lines3.n2.out
+tail -n +3 $INPUTS/ten.txt | head -n -5 > $OUT_DIR/ten.txt.skip-lines2.n-5.out
+tail -c +5 $INPUTS/ten.txt | head -c 7 > $OUT_DIR/ten.txt.skip-bytes4.c7.out
+tail -c +101 $INPUTS/binary.bin | head -c 200 > $OUT_DIR/binary.bin.skip-bytes100.c200.out
+
 ALL="$INPUTS/empty.txt $INPUTS/one.txt $INPUTS/two.txt $INPUTS/three.txt \
     $INPUTS/ten.txt"
 head      $ALL > $OUT_DIR/all.out
@@ -23,3 +61,37 @@ head -n 4 $ALL > $OUT_DIR/all.n4.out
 head -c 1 $ALL > $OUT_DIR/all.c1.out
 head -c 2 $ALL > $OUT_DIR/all.c2.out
 head -c 4 $ALL > $OUT_DIR/all.c4.out
+head -n -2 $ALL > $OUT_DIR/all.n-2.out
+head -c -3 $ALL > $OUT_DIR/all.c-3.out
+head -q $ALL > $OUT_DIR/all.q.out
+
+TAIL_DIR="$OUT_DIR/tailr"
+[[ ! -d "$TAIL_DIR" ]] && mkdir -p "$TAIL_DIR"
+
+for FILE in $INPUTS/*.txt; do
+    BASENAME=$(basename "$FILE")
+    tail       $FILE > ${TAIL_DIR}/${BASENAME}.out
+    tail -n 3  $FILE > ${TAIL_DIR}/${BASENAME}.n3.out
+    tail -n +3 $FILE > ${TAIL_DIR}/${BASENAME}.n+3.out
+    tail -c 5  $FILE > ${TAIL_DIR}/${BASENAME}.c5.out
+    tail -c +5 $FILE > ${TAIL_DIR}/${BASENAME}.c+5.out
+done
+
+tail -n 2 $INPUTS/binary.bin > $TAIL_DIR/binary.bin.n2.out
+tail -c 1b $INPUTS/binary.bin > $TAIL_DIR/binary.bin.c1b.out
+tail -z -n 2 $INPUTS/records.bin > $TAIL_DIR/records.bin.z.n2.out
+tail -z -n +2 $INPUTS/records.bin > $TAIL_DIR/records.bin.z.n+2.out
+tail      $ALL > $TAIL_DIR/all.out
+tail -n 0 $ALL > $TAIL_DIR/all.n0.out
+tail -n +2 $ALL > $TAIL_DIR/all.n+2.out
+tail -q -c 3 $ALL > $TAIL_DIR/all.q.c3.out
+printf '==> %s <==\n\n==> %s <==\n\xc3\x96\n==> %s <==\nT\n==> %s <==\nT\n==> %s <==\no' \
+    $ALL > $OUT_DIR/all.m1.out
+
+FIRST=1
+for FILE in $ALL; do
+    [[ $FIRST -eq 0 ]] && echo
+    FIRST=0
+    echo "==> $FILE <=="
+    tail -n +2 $FILE | head -n 1
+done > $OUT_DIR/all.skip-lines1.n1.out
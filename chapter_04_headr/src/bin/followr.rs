@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(e) = headr::follow::get_args().and_then(headr::follow::run) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
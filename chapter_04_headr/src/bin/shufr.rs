@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(e) = headr::shuf::get_args().and_then(headr::shuf::run) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(e) = headr::tail::get_args().and_then(headr::tail::run) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
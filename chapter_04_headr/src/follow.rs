@@ -0,0 +1,394 @@
+use crate::tail::{self, Input};
+use crate::{print_header, show_headers, MyResult};
+use clap::Arg;
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::thread;
+use std::time::Duration;
+
+// how much of what has been appended is read at a time
+const BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub struct Config {
+    tail: tail::Config,
+    by_name: bool,
+    retry: bool,
+    pid: Option<i32>,
+    sleep_interval: Duration,
+    inotify: bool,
+}
+
+// a file being followed. `missing` is set while the name refers to nothing,
+// though with --retry a file renamed away is still read until another takes
+// its place, and `gone` marks one that has been given up on.
+struct Followed {
+    name: String,
+    file: Option<File>,
+    identity: Option<(u64, u64)>,
+    position: u64,
+    missing: bool,
+    gone: bool,
+}
+
+pub fn get_args() -> MyResult<Config> {
+    let matches = tail::app("followr", "Rust tail -f")
+        .arg(
+            Arg::with_name("follow")
+                .short("f")
+                .long("follow")
+                .value_name("HOW")
+                .help("follow the open file as it grows (the default);\nwith =name, reopen the file by name so that\nlog rotation is followed")
+                .possible_values(&["descriptor", "name"])
+                .min_values(0)
+                .require_equals(true),
+        )
+        .arg(
+            Arg::with_name("follow_name_retry")
+                .short("F")
+                .help("same as --follow=name --retry"),
+        )
+        .arg(
+            Arg::with_name("retry")
+                .long("retry")
+                .help("keep trying to open a file if it is inaccessible"),
+        )
+        .arg(
+            Arg::with_name("pid")
+                .long("pid")
+                .value_name("PID")
+                .help("terminate after process ID, PID dies"),
+        )
+        .arg(
+            Arg::with_name("sleep_interval")
+                .short("s")
+                .long("sleep-interval")
+                .value_name("N")
+                .help("check the files and PID about every N seconds")
+                .default_value("1.0"),
+        )
+        .arg(
+            Arg::with_name("disable_inotify")
+                .long("disable-inotify")
+                .help("poll the files every interval instead of\nwaiting for inotify events"),
+        )
+        .get_matches();
+
+    let pid = matches
+        .value_of("pid")
+        .map(parse_pid)
+        .transpose()
+        .map_err(|e| format!("illegal pid -- {}", e))?;
+
+    let sleep_interval = matches
+        .value_of("sleep_interval")
+        .map(parse_interval)
+        .transpose()
+        .map_err(|e| format!("illegal sleep interval -- {}", e))?;
+
+    let name_retry = matches.is_present("follow_name_retry");
+    Ok(Config {
+        tail: tail::config_from(&matches)?,
+        by_name: name_retry || matches.value_of("follow") == Some("name"),
+        retry: name_retry || matches.is_present("retry"),
+        pid,
+        sleep_interval: sleep_interval.unwrap(),
+        inotify: !matches.is_present("disable_inotify"),
+    })
+}
+
+fn parse_pid(val: &str) -> MyResult<i32> {
+    match val.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(From::from(val)),
+    }
+}
+
+fn parse_interval(val: &str) -> MyResult<Duration> {
+    val.parse()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| From::from(val))
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    let files = &config.tail.files;
+    let headers = show_headers(config.tail.quiet, config.tail.verbose, files.len());
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    // the tail of each file is shown as by tailr; regular files are then
+    // followed, while pipes and stdin are shown once
+    let mut followed = Vec::new();
+    let mut last_shown = None;
+    for (file_num, filename) in files.iter().enumerate() {
+        let mut entry = Followed::new(filename);
+        match tail::open_input(filename) {
+            Err(err) => {
+                eprintln!("{}: {}", filename, err);
+                entry.missing = true;
+                entry.gone = !config.retry;
+            }
+            Ok(input) => {
+                if headers {
+                    print_header(filename, file_num == 0);
+                    last_shown = Some(file_num);
+                }
+                match input {
+                    Input::File(mut file) => {
+                        tail::tail_file(&mut file, &config.tail, &mut out)?;
+                        entry.follow(file)?;
+                    }
+                    Input::Stream(file) => {
+                        tail::tail_stream(file, &config.tail, &mut out)?;
+                        continue;
+                    }
+                }
+            }
+        }
+        followed.push(entry);
+    }
+    out.flush()?;
+    if followed.is_empty() {
+        return Ok(());
+    }
+
+    let mut waiter = Waiter::new(config.inotify);
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        // data written before the process died is still shown
+        let dead = config.pid.is_some_and(|pid| !process_alive(pid));
+
+        for (file_num, entry) in followed.iter_mut().enumerate() {
+            if entry.gone {
+                continue;
+            }
+            // what was written to a rotated file before the switch comes
+            // first, and the header only once there is something to show
+            entry.check_truncated()?;
+            let mut reopened = false;
+            loop {
+                let len = entry.read_some(&mut buffer)?;
+                if len == 0 {
+                    if !reopened && entry.reopen(&config) {
+                        reopened = true;
+                        continue;
+                    }
+                    break;
+                }
+                if headers && last_shown != Some(file_num) {
+                    print_header(&entry.name, last_shown.is_none());
+                    last_shown = Some(file_num);
+                }
+                out.write_all(&buffer[..len])?;
+            }
+        }
+        out.flush()?;
+
+        if dead {
+            return Ok(());
+        }
+        if followed.iter().all(|entry| entry.gone) {
+            return Err(From::from("no files remaining"));
+        }
+        waiter.wait(&followed, &config)?;
+    }
+}
+
+impl Followed {
+    fn new(name: &str) -> Self {
+        Followed {
+            name: name.to_string(),
+            file: None,
+            identity: None,
+            position: 0,
+            missing: false,
+            gone: false,
+        }
+    }
+
+    // follows an open file from where it now stands
+    fn follow(&mut self, mut file: File) -> io::Result<()> {
+        self.position = file.stream_position()?;
+        self.identity = identity(&file.metadata()?);
+        self.file = Some(file);
+        Ok(())
+    }
+
+    // starts over if the file shrank since it was last read
+    fn check_truncated(&mut self) -> io::Result<()> {
+        if let Some(file) = &mut self.file {
+            if file.metadata()?.len() < self.position {
+                eprintln!("{}: file truncated", self.name);
+                file.seek(SeekFrom::Start(0))?;
+                self.position = 0;
+            }
+        }
+        Ok(())
+    }
+
+    // reads the next piece of whatever has been appended, returning 0 once
+    // all of it has been read
+    fn read_some(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => return Ok(0),
+        };
+        loop {
+            match file.read(buffer) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                result => {
+                    let len = result?;
+                    self.position += len as u64;
+                    return Ok(len);
+                }
+            }
+        }
+    }
+
+    // opens the name again when following by name and it now refers to a
+    // different file, or when --retry is waiting for it to appear; returns
+    // whether there is a new file to read
+    fn reopen(&mut self, config: &Config) -> bool {
+        if self.file.is_some() && !config.by_name {
+            return false;
+        }
+        let meta = match fs::metadata(&self.name) {
+            Ok(meta) => meta,
+            Err(err) => {
+                if !self.missing {
+                    eprintln!("{}: has become inaccessible: {}", self.name, err);
+                    self.missing = true;
+                    if !config.retry {
+                        self.file = None;
+                        self.gone = true;
+                    }
+                }
+                return false;
+            }
+        };
+        if self.file.is_some() && identity(&meta) == self.identity {
+            self.missing = false;
+            return false;
+        }
+        let message = match self.missing {
+            true => "has appeared",
+            false => "has been replaced",
+        };
+        match File::open(&self.name).and_then(|file| self.follow(file)) {
+            Ok(()) => {
+                eprintln!("{}: {}; following new file", self.name, message);
+                self.missing = false;
+                true
+            }
+            // it may be back by the next check
+            Err(_) => false,
+        }
+    }
+}
+
+// a device and inode pair telling whether a name still refers to the file
+// that was opened
+#[cfg(unix)]
+fn identity(meta: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(_meta: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(unix)]
+fn process_alive(pid: i32) -> bool {
+    // signal 0 checks that the process exists without disturbing it
+    let found = unsafe { libc::kill(pid, 0) } == 0;
+    found || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: i32) -> bool {
+    true
+}
+
+// waits until a followed file may have changed: on Linux that is an inotify
+// event or the sleep interval, whichever comes first, and elsewhere (or when
+// inotify is disabled or unavailable) simply the sleep interval
+enum Waiter {
+    #[cfg(target_os = "linux")]
+    Inotify(inotify::Inotify),
+    Sleep,
+}
+
+impl Waiter {
+    #[cfg(target_os = "linux")]
+    fn new(use_inotify: bool) -> Self {
+        match inotify::Inotify::init() {
+            Ok(inotify) if use_inotify => Waiter::Inotify(inotify),
+            _ => Waiter::Sleep,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new(_use_inotify: bool) -> Self {
+        Waiter::Sleep
+    }
+
+    fn wait(&mut self, followed: &[Followed], config: &Config) -> io::Result<()> {
+        match self {
+            #[cfg(target_os = "linux")]
+            Waiter::Inotify(inotify) => wait_inotify(inotify, followed, config),
+            Waiter::Sleep => {
+                thread::sleep(config.sleep_interval);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn wait_inotify(
+    inotify: &mut inotify::Inotify,
+    followed: &[Followed],
+    config: &Config,
+) -> io::Result<()> {
+    use inotify::WatchMask;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    // watches are added again each time, as rotation brings new files; a
+    // name that is missing right now is still picked up by the interval
+    let mut watches = inotify.watches();
+    for entry in followed.iter().filter(|entry| !entry.gone) {
+        let _ = watches.add(
+            &entry.name,
+            WatchMask::MODIFY | WatchMask::ATTRIB | WatchMask::DELETE_SELF | WatchMask::MOVE_SELF,
+        );
+        if config.by_name || entry.file.is_none() {
+            let dir = match Path::new(&entry.name).parent() {
+                Some(dir) if dir != Path::new("") => dir,
+                _ => Path::new("."),
+            };
+            let _ = watches.add(dir, WatchMask::CREATE | WatchMask::MOVED_TO);
+        }
+    }
+
+    let mut poll_fd = libc::pollfd {
+        fd: inotify.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout = config.sleep_interval.as_millis().min(i32::MAX as u128) as i32;
+    // an interrupted poll only means the files are checked a little early
+    unsafe { libc::poll(&mut poll_fd, 1, timeout) };
+
+    let mut buffer = [0; 4096];
+    loop {
+        match inotify.read_events(&mut buffer) {
+            Ok(_) => continue,
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(err) => return Err(err),
+        }
+    }
+}
@@ -1,15 +1,46 @@
+use crate::Count::*;
 use clap::{App, Arg};
+use regex::bytes::Regex;
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::num::IntErrorKind;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub mod follow;
+pub mod shuf;
+pub mod tail;
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
+const SUFFIX_HELP: &str =
+    "K may have a multiplier suffix: b 512, kB 1000, K 1024, MB 1000*1000,\n\
+     M 1024*1024, GB 1000*1000*1000, G 1024*1024*1024, and so on for T, P,\n\
+     E, Z, Y. Binary prefixes can be used, too: KiB=K, MiB=M, and so on.";
+
+// how much of each file to print: the first K lines or bytes, or with a
+// leading '-' on the command line, all but the last K
+#[derive(Debug, PartialEq)]
+pub enum Count {
+    First(usize),
+    AllButLast(usize),
+}
+
 #[derive(Debug)]
 pub struct Config {
     files: Vec<String>,
-    lines: usize,
-    bytes: Option<usize>,
+    lines: Count,
+    bytes: Option<Count>,
+    chars: Option<usize>,
+    graphemes: bool,
+    quiet: bool,
+    verbose: bool,
+    terminator: u8,
+    until: Option<Regex>,
+    until_inclusive: bool,
+    skip_bytes: Option<u64>,
+    skip_lines: usize,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -17,6 +48,7 @@ pub fn get_args() -> MyResult<Config> {
         .version("0.1.0")
         .author("Ken Youens-Clark <kyclark@gmail.com>")
         .about("Rust head")
+        .after_help(SUFFIX_HELP)
         .arg(
             Arg::with_name("files")
                 .value_name("FILE")
@@ -30,6 +62,7 @@ pub fn get_args() -> MyResult<Config> {
                 .long("lines")
                 .value_name("LINES")
                 .help("print the first K lines instead of the first 10;\nwith the leading '-', print all but the last\nK lines of each file")
+                .allow_hyphen_values(true)
                 .default_value("10"),
         )
         .arg(
@@ -38,37 +71,193 @@ pub fn get_args() -> MyResult<Config> {
                 .long("bytes")
                 .value_name("BYTES")
                 .help("print the first K bytes of each file;\nwith the leading '-', print all but the last\nK bytes of each file")
+                .allow_hyphen_values(true)
                 .takes_value(true)
                 .conflicts_with("lines"),
         )
+        .arg(
+            Arg::with_name("chars")
+                .short("m")
+                .long("chars")
+                .value_name("CHARS")
+                .help("print the first K characters of each file,\nleaving multi-byte characters whole")
+                .conflicts_with_all(&["lines", "bytes"]),
+        )
+        .arg(
+            Arg::with_name("graphemes")
+                .long("graphemes")
+                .help("with -m, count user-perceived characters\n(grapheme clusters) instead")
+                .requires("chars"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .alias("silent")
+                .help("never print headers giving file names")
+                .overrides_with("verbose"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("always print headers giving file names")
+                .overrides_with("quiet"),
+        )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .short("z")
+                .long("zero-terminated")
+                .help("line delimiter is NUL, not newline"),
+        )
+        .arg(
+            Arg::with_name("until")
+                .long("until")
+                .value_name("REGEX")
+                .help("stop before the first line matching REGEX;\nwith -n, print no more than K lines")
+                .conflicts_with_all(&["bytes", "chars"]),
+        )
+        .arg(
+            Arg::with_name("until_inclusive")
+                .long("until-inclusive")
+                .help("print the line matching the --until REGEX\nas the last one")
+                .requires("until"),
+        )
+        .arg(
+            Arg::with_name("skip_bytes")
+                .long("skip-bytes")
+                .value_name("N")
+                .help("start each file N bytes in")
+                .conflicts_with("skip_lines"),
+        )
+        .arg(
+            Arg::with_name("skip_lines")
+                .long("skip-lines")
+                .value_name("N")
+                .help("start each file after its first N lines"),
+        )
         .get_matches();
 
-    let lines = matches
-        .value_of("lines")
-        .map(parse_positive_int)
+    let skip_bytes = matches
+        .value_of("skip_bytes")
+        .map(parse_size)
+        .transpose()
+        .map_err(|e| format!("illegal byte offset -- {}", e))?;
+
+    let skip_lines = matches
+        .value_of("skip_lines")
+        .map(parse_size)
+        .transpose()
+        .map_err(|e| format!("illegal line offset -- {}", e))?;
+
+    let until = matches
+        .value_of("until")
+        .map(|val| Regex::new(val).map_err(|_| val))
         .transpose()
-        .map_err(|e| format!("illegal line count -- {}", e))?;
+        .map_err(|e| format!("illegal regex -- {}", e))?;
+
+    // --until alone reads as far as the match, however far that is
+    let lines = match matches.occurrences_of("lines") {
+        0 if until.is_some() => Some(First(usize::MAX)),
+        _ => matches
+            .value_of("lines")
+            .map(parse_count)
+            .transpose()
+            .map_err(|e| format!("illegal line count -- {}", e))?,
+    };
 
     let bytes = matches
         .value_of("bytes")
-        .map(parse_positive_int)
+        .map(parse_count)
         .transpose()
         .map_err(|e| format!("illegal byte count -- {}", e))?;
 
+    let chars = matches
+        .value_of("chars")
+        .map(parse_positive_int)
+        .transpose()
+        .map_err(|e| format!("illegal character count -- {}", e))?;
+
     Ok(Config {
         files: matches.values_of_lossy("files").unwrap(),
         lines: lines.unwrap(),
-        bytes
+        bytes,
+        chars,
+        graphemes: matches.is_present("graphemes"),
+        quiet: matches.is_present("quiet"),
+        verbose: matches.is_present("verbose"),
+        terminator: if matches.is_present("zero_terminated") { b'\0' } else { b'\n' },
+        until,
+        until_inclusive: matches.is_present("until_inclusive"),
+        skip_bytes: skip_bytes.map(|n| n as u64),
+        skip_lines: skip_lines.unwrap_or(0),
     })
 }
 
 fn parse_positive_int(val: &str) -> MyResult<usize> {
-    match val.parse() {
-        Ok(n) if n > 0 => Ok(n),
+    match parse_size(val)? {
+        0 => Err(From::from(val)),
+        n => Ok(n),
+    }
+}
+
+// a non-negative integer with an optional GNU-style multiplier suffix: b is
+// 512, K, M, G and so on are powers of 1024 (also written KiB, MiB, ...),
+// and KB, MB, GB, ... are powers of 1000
+fn parse_size(val: &str) -> MyResult<usize> {
+    let split = val
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(val.len());
+    let (digits, suffix) = val.split_at(split);
+    let (base, exp) = match parse_suffix(suffix) {
+        Some(multiplier) => multiplier,
+        None => return Err(From::from(val)),
+    };
+    let too_large = || From::from(format!("{}: value too large", val));
+    match digits.parse::<usize>() {
+        Ok(n) => base
+            .checked_pow(exp)
+            .and_then(|multiplier| n.checked_mul(multiplier))
+            .ok_or_else(too_large),
+        Err(e) if *e.kind() == IntErrorKind::PosOverflow => Err(too_large()),
         _ => Err(From::from(val)),
     }
 }
 
+fn parse_suffix(suffix: &str) -> Option<(usize, u32)> {
+    match suffix {
+        "" => return Some((1, 1)),
+        "b" => return Some((512, 1)),
+        _ => {}
+    }
+    let mut chars = suffix.chars();
+    let exp = match chars.next()? {
+        'k' | 'K' => 1,
+        'm' | 'M' => 2,
+        'G' => 3,
+        'T' => 4,
+        'P' => 5,
+        'E' => 6,
+        'Z' => 7,
+        'Y' => 8,
+        _ => return None,
+    };
+    match chars.as_str() {
+        "" | "iB" => Some((1024, exp)),
+        "B" => Some((1000, exp)),
+        _ => None,
+    }
+}
+
+fn parse_count(val: &str) -> MyResult<Count> {
+    match val.strip_prefix('-') {
+        Some(num) => parse_positive_int(num)
+            .map(AllButLast)
+            .map_err(|e| From::from(format!("-{}", e))),
+        None => parse_positive_int(val).map(First),
+    }
+}
+
 fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
     match filename {
         "-" => Ok(Box::new(BufReader::new(io::stdin()))),
@@ -76,42 +265,233 @@ fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
     }
 }
 
+// opens a file past --skip-bytes or --skip-lines: a regular file seeks
+// straight to the offset, while anything else is read through and the
+// prefix dropped
+fn open_from(filename: &str, config: &Config) -> MyResult<Box<dyn BufRead>> {
+    let mut skip_bytes = config.skip_bytes;
+    let mut file: Box<dyn BufRead> = match filename {
+        "-" => open(filename)?,
+        _ => {
+            let mut file = File::open(filename)?;
+            if let Some(offset) = skip_bytes {
+                if file.metadata()?.is_file() {
+                    file.seek(SeekFrom::Start(offset))?;
+                    skip_bytes = None;
+                }
+            }
+            Box::new(BufReader::new(file))
+        }
+    };
+    if let Some(offset) = skip_bytes {
+        io::copy(&mut file.by_ref().take(offset), &mut io::sink())?;
+    }
+    for _ in 0..config.skip_lines {
+        if file.skip_until(config.terminator)? == 0 {
+            break;
+        }
+    }
+    Ok(file)
+}
+
 pub fn run(config: Config) -> MyResult<()> {
-    let num_files = config.files.len();
+    let headers = show_headers(config.quiet, config.verbose, config.files.len());
 
     for (file_num, filename) in config.files.iter().enumerate() {
-        match open(&filename) {
+        match open_from(filename, &config) {
             Err(err) => eprintln!("{}: {}", filename, err),
-            Ok(mut file) => {
-                if num_files > 1 {
-                    println!(
-                        "{}==> {} <==",
-                        if file_num > 0 { "\n" } else { "" },
-                        filename
-                    );
+            Ok(file) => {
+                if headers {
+                    print_header(filename, file_num == 0);
                 }
 
-                if let Some(num_bytes) = config.bytes {
-                    let mut handle = file.take(num_bytes as u64);
-                    let mut buffer = vec![0; num_bytes];
-                    let bytes_read = handle.read(&mut buffer)?;
-                    print!(
-                        "{}",
-                        String::from_utf8_lossy(&buffer[..bytes_read])
-                    );
-                } else {
-                    let mut line = String::new();
-                    for _ in 0..config.lines {
-                        let bytes = file.read_line(&mut line)?;
-                        if bytes == 0 {
-                            break;
+                if let Some(num_chars) = config.chars {
+                    first_chars(file, num_chars, config.graphemes)?;
+                    continue;
+                }
+                match &config.bytes {
+                    Some(First(num_bytes)) => first_bytes(file, *num_bytes)?,
+                    Some(AllButLast(num_bytes)) => all_but_last_bytes(file, *num_bytes)?,
+                    None => match config.lines {
+                        First(num_lines) => first_lines(file, num_lines, &config)?,
+                        AllButLast(num_lines) => {
+                            all_but_last_lines(file, num_lines, &config)?
                         }
-                        print!("{}", line);
-                        line.clear();
-                    }
+                    },
                 }
             }
         };
     }
     Ok(())
 }
+
+// headers go out when there are several files, unless -q or -v says otherwise
+fn show_headers(quiet: bool, verbose: bool, num_files: usize) -> bool {
+    verbose || (!quiet && num_files > 1)
+}
+
+fn print_header(filename: &str, first: bool) {
+    println!("{}==> {} <==", if first { "" } else { "\n" }, filename);
+}
+
+// copies exactly the first `num_bytes` raw bytes, a buffer at a time, so
+// binary data comes through untouched and large counts cost no memory
+fn first_bytes(file: impl BufRead, num_bytes: usize) -> MyResult<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    io::copy(&mut file.take(num_bytes as u64), &mut out)?;
+    Ok(())
+}
+
+// copies the first `num_chars` characters, or grapheme clusters, a buffer
+// at a time. The last two units read are held back until more input shows
+// whether it extends them: a character may be cut off at the end of the
+// buffer, and a combining mark may follow.
+fn first_chars(mut file: impl BufRead, num_chars: usize, graphemes: bool) -> MyResult<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut pending = Vec::new();
+    let mut remaining = num_chars;
+    while remaining > 0 {
+        let chunk = file.fill_buf()?;
+        let done = chunk.is_empty();
+        let len = chunk.len();
+        pending.extend_from_slice(chunk);
+        file.consume(len);
+
+        let ends = unit_ends(&pending, graphemes);
+        let complete = match done {
+            true => ends.len(),
+            false => ends.len().saturating_sub(2),
+        };
+        let count = complete.min(remaining);
+        if count > 0 {
+            let end = ends[count - 1];
+            out.write_all(&pending[..end])?;
+            pending.drain(..end);
+            remaining -= count;
+        }
+        if done {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// the offset at the end of each character, or grapheme cluster, in `bytes`;
+// an invalid UTF-8 sequence counts as one and is passed through as it is
+fn unit_ends(bytes: &[u8], graphemes: bool) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut offset = 0;
+    for chunk in bytes.utf8_chunks() {
+        let valid = chunk.valid();
+        if graphemes {
+            ends.extend(
+                valid
+                    .grapheme_indices(true)
+                    .map(|(start, grapheme)| offset + start + grapheme.len()),
+            );
+        } else {
+            ends.extend(
+                valid
+                    .char_indices()
+                    .map(|(start, c)| offset + start + c.len_utf8()),
+            );
+        }
+        offset += valid.len();
+        if !chunk.invalid().is_empty() {
+            offset += chunk.invalid().len();
+            ends.push(offset);
+        }
+    }
+    ends
+}
+
+// reads the next line into `line`, returning false at the end of the input
+// or, with --until, once the matching line has been reached; that line is
+// only returned with --until-inclusive
+fn next_line(
+    file: &mut impl BufRead,
+    line: &mut Vec<u8>,
+    config: &Config,
+    stopped: &mut bool,
+) -> io::Result<bool> {
+    if *stopped || file.read_until(config.terminator, line)? == 0 {
+        return Ok(false);
+    }
+    if let Some(until) = &config.until {
+        let content = line.strip_suffix(&[config.terminator]).unwrap_or(line);
+        if until.is_match(content) {
+            *stopped = true;
+            return Ok(config.until_inclusive);
+        }
+    }
+    Ok(true)
+}
+
+// prints the first `num_lines` lines, each ended by the terminator
+fn first_lines(mut file: impl BufRead, num_lines: usize, config: &Config) -> MyResult<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut line = Vec::new();
+    let mut stopped = false;
+    for _ in 0..num_lines {
+        if !next_line(&mut file, &mut line, config, &mut stopped)? {
+            break;
+        }
+        out.write_all(&line)?;
+        line.clear();
+    }
+    Ok(())
+}
+
+// prints all but the last `num_lines` lines, holding back only that many
+// lines at a time so that input of any size can be streamed
+fn all_but_last_lines(mut file: impl BufRead, num_lines: usize, config: &Config) -> MyResult<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut held: VecDeque<Vec<u8>> = VecDeque::new();
+    let mut stopped = false;
+    loop {
+        let mut line = Vec::new();
+        if !next_line(&mut file, &mut line, config, &mut stopped)? {
+            break;
+        }
+        held.push_back(line);
+        if held.len() > num_lines {
+            if let Some(line) = held.pop_front() {
+                out.write_all(&line)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// prints all but the last `num_bytes` bytes, keeping only that many in a
+// ring buffer while reading
+fn all_but_last_bytes(mut file: impl BufRead, num_bytes: usize) -> MyResult<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut held: VecDeque<u8> = VecDeque::new();
+    loop {
+        let chunk = file.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        let len = chunk.len();
+        held.extend(chunk);
+        file.consume(len);
+        if held.len() > num_bytes {
+            let excess = held.len() - num_bytes;
+            let (front, back) = held.as_slices();
+            if excess <= front.len() {
+                out.write_all(&front[..excess])?;
+            } else {
+                out.write_all(front)?;
+                out.write_all(&back[..excess - front.len()])?;
+            }
+            held.drain(..excess);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use crate::{open, parse_size, MyResult};
+use clap::{App, Arg};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug)]
+pub struct Config {
+    files: Vec<String>,
+    sample: Option<usize>,
+    seed: Option<u64>,
+    terminator: u8,
+}
+
+pub fn get_args() -> MyResult<Config> {
+    let matches = App::new("shufr")
+        .version("0.1.0")
+        .author("Ken Youens-Clark <kyclark@gmail.com>")
+        .about("Rust shuf")
+        .arg(
+            Arg::with_name("files")
+                .value_name("FILE")
+                .help("Input file(s)")
+                .multiple(true)
+                .default_value("-"),
+        )
+        .arg(
+            Arg::with_name("sample")
+                .short("n")
+                .long("sample")
+                .value_name("K")
+                .help("print a uniform random sample of K lines\ninstead of shuffling them all"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("seed the random generator so that runs repeat"),
+        )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .short("z")
+                .long("zero-terminated")
+                .help("line delimiter is NUL, not newline"),
+        )
+        .get_matches();
+
+    let sample = matches
+        .value_of("sample")
+        .map(parse_size)
+        .transpose()
+        .map_err(|e| format!("illegal sample size -- {}", e))?;
+
+    let seed = matches
+        .value_of("seed")
+        .map(|val| val.parse().map_err(|_| val))
+        .transpose()
+        .map_err(|e| format!("illegal seed -- {}", e))?;
+
+    Ok(Config {
+        files: matches.values_of_lossy("files").unwrap(),
+        sample,
+        seed,
+        terminator: if matches.is_present("zero_terminated") {
+            b'\0'
+        } else {
+            b'\n'
+        },
+    })
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    // a named generator, unlike StdRng, gives the same sequence for a seed
+    // whatever the version of rand or the platform
+    let mut rng = match config.seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
+    };
+    let mut sampler = Sampler::new(config.sample);
+
+    for filename in &config.files {
+        match open(filename) {
+            Err(err) => eprintln!("{}: {}", filename, err),
+            Ok(mut file) => loop {
+                let mut line = Vec::new();
+                if file.read_until(config.terminator, &mut line)? == 0 {
+                    break;
+                }
+                if line.last() != Some(&config.terminator) {
+                    line.push(config.terminator);
+                }
+                sampler.add(line, &mut rng);
+            },
+        }
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in sampler.finish(&mut rng) {
+        out.write_all(&line)?;
+    }
+    Ok(())
+}
+
+// keeps either every line, to be shuffled at the end, or a reservoir of K
+// of them: each line replaces a random one of the K with a chance of K/N,
+// where N is the number seen so far, so that every line of a stream of any
+// length is equally likely to be in the sample
+struct Sampler {
+    size: Option<usize>,
+    seen: usize,
+    lines: Vec<Vec<u8>>,
+}
+
+impl Sampler {
+    fn new(size: Option<usize>) -> Self {
+        Sampler {
+            size,
+            seen: 0,
+            lines: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, line: Vec<u8>, rng: &mut impl Rng) {
+        self.seen += 1;
+        match self.size {
+            Some(size) if self.lines.len() >= size => {
+                let slot = rng.gen_range(0..self.seen);
+                if slot < size {
+                    self.lines[slot] = line;
+                }
+            }
+            _ => self.lines.push(line),
+        }
+    }
+
+    // the reservoir keeps lines in the order they were read until they are
+    // replaced, so a sample is shuffled too
+    fn finish(mut self, rng: &mut impl Rng) -> Vec<Vec<u8>> {
+        self.lines.shuffle(rng);
+        self.lines
+    }
+}
+
+// --------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::Sampler;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_sampler_is_uniform() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut counts = [0; 10];
+        for _ in 0..10_000 {
+            let mut sampler = Sampler::new(Some(3));
+            for n in 0..10u8 {
+                sampler.add(vec![n], &mut rng);
+            }
+            for line in sampler.finish(&mut rng) {
+                counts[line[0] as usize] += 1;
+            }
+        }
+        // each line should be picked about 3,000 times
+        for count in counts {
+            assert!((2_700..3_300).contains(&count), "{:?}", counts);
+        }
+    }
+}
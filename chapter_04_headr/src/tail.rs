@@ -0,0 +1,322 @@
+use crate::tail::Count::*;
+use crate::{open, parse_size, print_header, show_headers, MyResult, SUFFIX_HELP};
+use clap::{App, Arg, ArgMatches};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+// how much is read at a time when searching backwards from the end of a file
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// where the output starts: the last K lines or bytes, or with a leading '+'
+// on the command line, the Kth one from the start
+#[derive(Debug, PartialEq)]
+pub enum Count {
+    Last(usize),
+    StartAt(usize),
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub(crate) files: Vec<String>,
+    lines: Count,
+    bytes: Option<Count>,
+    pub(crate) quiet: bool,
+    pub(crate) verbose: bool,
+    terminator: u8,
+}
+
+// regular files are read backwards from the end; anything else is streamed
+pub(crate) enum Input {
+    File(File),
+    Stream(Box<dyn BufRead>),
+}
+
+pub fn get_args() -> MyResult<Config> {
+    config_from(&app("tailr", "Rust tail").get_matches())
+}
+
+// the arguments tailr shares with followr
+pub(crate) fn app(name: &str, about: &'static str) -> App<'static, 'static> {
+    App::new(name)
+        .version("0.1.0")
+        .author("Ken Youens-Clark <kyclark@gmail.com>")
+        .about(about)
+        .after_help(SUFFIX_HELP)
+        .arg(
+            Arg::with_name("files")
+                .value_name("FILE")
+                .help("Input file(s)")
+                .multiple(true)
+                .default_value("-"),
+        )
+        .arg(
+            Arg::with_name("lines")
+                .short("n")
+                .long("lines")
+                .value_name("LINES")
+                .help("output the last K lines, instead of the last 10;\nor use -n +K to output starting with the Kth")
+                .allow_hyphen_values(true)
+                .default_value("10"),
+        )
+        .arg(
+            Arg::with_name("bytes")
+                .short("c")
+                .long("bytes")
+                .value_name("BYTES")
+                .help("output the last K bytes; or use -c +K to output\nbytes starting with the Kth of each file")
+                .allow_hyphen_values(true)
+                .takes_value(true)
+                .conflicts_with("lines"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .alias("silent")
+                .help("never print headers giving file names")
+                .overrides_with("verbose"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("always print headers giving file names")
+                .overrides_with("quiet"),
+        )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .short("z")
+                .long("zero-terminated")
+                .help("line delimiter is NUL, not newline"),
+        )
+}
+
+pub(crate) fn config_from(matches: &ArgMatches) -> MyResult<Config> {
+    let lines = matches
+        .value_of("lines")
+        .map(parse_count)
+        .transpose()
+        .map_err(|e| format!("illegal line count -- {}", e))?;
+
+    let bytes = matches
+        .value_of("bytes")
+        .map(parse_count)
+        .transpose()
+        .map_err(|e| format!("illegal byte count -- {}", e))?;
+
+    Ok(Config {
+        files: matches.values_of_lossy("files").unwrap(),
+        lines: lines.unwrap(),
+        bytes,
+        quiet: matches.is_present("quiet"),
+        verbose: matches.is_present("verbose"),
+        terminator: if matches.is_present("zero_terminated") {
+            b'\0'
+        } else {
+            b'\n'
+        },
+    })
+}
+
+fn parse_count(val: &str) -> MyResult<Count> {
+    if let Some(num) = val.strip_prefix('+') {
+        return parse_size(num)
+            .map(StartAt)
+            .map_err(|e| From::from(format!("+{}", e)));
+    }
+    match val.strip_prefix('-') {
+        Some(num) => parse_size(num)
+            .map(Last)
+            .map_err(|e| From::from(format!("-{}", e))),
+        None => parse_size(val).map(Last),
+    }
+}
+
+pub(crate) fn open_input(filename: &str) -> MyResult<Input> {
+    match filename {
+        "-" => open(filename).map(Input::Stream),
+        _ => {
+            let file = File::open(filename)?;
+            if file.metadata()?.is_file() {
+                Ok(Input::File(file))
+            } else {
+                Ok(Input::Stream(Box::new(BufReader::new(file))))
+            }
+        }
+    }
+}
+
+pub fn run(config: Config) -> MyResult<()> {
+    // like tail, asking for nothing prints nothing, not even the headers
+    if config.bytes.as_ref().unwrap_or(&config.lines) == &Last(0) {
+        return Ok(());
+    }
+    let headers = show_headers(config.quiet, config.verbose, config.files.len());
+
+    for (file_num, filename) in config.files.iter().enumerate() {
+        match open_input(filename) {
+            Err(err) => eprintln!("{}: {}", filename, err),
+            Ok(input) => {
+                if headers {
+                    print_header(filename, file_num == 0);
+                }
+
+                let stdout = io::stdout();
+                let mut out = stdout.lock();
+                match input {
+                    Input::File(mut file) => tail_file(&mut file, &config, &mut out)?,
+                    Input::Stream(file) => tail_stream(file, &config, &mut out)?,
+                }
+            }
+        };
+    }
+    Ok(())
+}
+
+// seeks straight to where the output starts, so only the tail is read,
+// and leaves the file at its end
+pub(crate) fn tail_file(file: &mut File, config: &Config, out: &mut impl Write) -> MyResult<()> {
+    let len = file.metadata()?.len();
+    let start = match &config.bytes {
+        Some(Last(num_bytes)) => len.saturating_sub(*num_bytes as u64),
+        Some(StartAt(num_bytes)) => num_bytes.saturating_sub(1) as u64,
+        None => match &config.lines {
+            Last(num_lines) => last_lines_start(file, len, *num_lines, config.terminator)?,
+            StartAt(_) => return tail_stream(BufReader::new(file), config, out),
+        },
+    };
+    file.seek(SeekFrom::Start(start))?;
+    io::copy(file, out)?;
+    Ok(())
+}
+
+// finds where the last `num_lines` lines begin by reading backwards from
+// `len` a chunk at a time. A terminator at the very end closes the last
+// line rather than starting another one.
+fn last_lines_start(
+    file: &mut (impl Read + Seek),
+    len: u64,
+    num_lines: usize,
+    terminator: u8,
+) -> io::Result<u64> {
+    if num_lines == 0 {
+        return Ok(len);
+    }
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut found = 0;
+    let mut end = len;
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK_SIZE as u64);
+        let chunk = &mut buffer[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        for (i, &byte) in chunk.iter().enumerate().rev() {
+            let pos = start + i as u64;
+            if byte == terminator && pos + 1 != len {
+                found += 1;
+                if found == num_lines {
+                    return Ok(pos + 1);
+                }
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+// input that cannot seek is read once from the start, holding back no more
+// than the requested number of lines or bytes
+pub(crate) fn tail_stream(
+    mut file: impl BufRead,
+    config: &Config,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    match &config.bytes {
+        Some(Last(num_bytes)) => last_bytes(file, *num_bytes, out)?,
+        Some(StartAt(num_bytes)) => {
+            let skip = num_bytes.saturating_sub(1) as u64;
+            io::copy(&mut file.by_ref().take(skip), &mut io::sink())?;
+            io::copy(&mut file, out)?;
+        }
+        None => match &config.lines {
+            Last(num_lines) => last_lines(file, *num_lines, config.terminator, out)?,
+            StartAt(num_lines) => {
+                for _ in 1..*num_lines {
+                    if file.skip_until(config.terminator)? == 0 {
+                        break;
+                    }
+                }
+                io::copy(&mut file, out)?;
+            }
+        },
+    }
+    Ok(())
+}
+
+fn last_lines(
+    mut file: impl BufRead,
+    num_lines: usize,
+    terminator: u8,
+    out: &mut impl Write,
+) -> MyResult<()> {
+    let mut held: VecDeque<Vec<u8>> = VecDeque::new();
+    loop {
+        let mut line = Vec::new();
+        if file.read_until(terminator, &mut line)? == 0 {
+            break;
+        }
+        held.push_back(line);
+        if held.len() > num_lines {
+            held.pop_front();
+        }
+    }
+    for line in held {
+        out.write_all(&line)?;
+    }
+    Ok(())
+}
+
+fn last_bytes(mut file: impl BufRead, num_bytes: usize, out: &mut impl Write) -> MyResult<()> {
+    let mut held: VecDeque<u8> = VecDeque::new();
+    loop {
+        let chunk = file.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        let len = chunk.len();
+        held.extend(chunk);
+        file.consume(len);
+        if held.len() > num_bytes {
+            held.drain(..held.len() - num_bytes);
+        }
+    }
+    let (front, back) = held.as_slices();
+    out.write_all(front)?;
+    out.write_all(back)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::{last_lines_start, CHUNK_SIZE};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_last_lines_start() {
+        let text: String = (0..20_000).map(|n| format!("line {}\n", n)).collect();
+        assert!(text.len() > 2 * CHUNK_SIZE);
+        let len = text.len() as u64;
+        let mut file = Cursor::new(text.as_bytes());
+        let start = |file: &mut Cursor<&[u8]>, num| {
+            last_lines_start(file, len, num, b'\n').unwrap() as usize
+        };
+        assert_eq!(start(&mut file, 0), text.len());
+        assert_eq!(&text[start(&mut file, 1)..], "line 19999\n");
+        assert_eq!(&text[start(&mut file, 2)..], "line 19998\nline 19999\n");
+        assert!(text[start(&mut file, 15_000)..].starts_with("line 5000\n"));
+        assert_eq!(start(&mut file, 20_000), 0);
+        assert_eq!(start(&mut file, 50_000), 0);
+    }
+}
@@ -1,11 +1,7 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
-use std::io::prelude::*;
-use std::{
-    error::Error,
-    fs::{self, File},
-};
+use std::{error::Error, fs};
 
 type TestResult = Result<(), Box<dyn Error>>;
 
@@ -15,6 +11,10 @@ const ONE: &str = "./tests/inputs/one.txt";
 const TWO: &str = "./tests/inputs/two.txt";
 const THREE: &str = "./tests/inputs/three.txt";
 const TEN: &str = "./tests/inputs/ten.txt";
+const BINARY: &str = "./tests/inputs/binary.bin";
+const RECORDS: &str = "./tests/inputs/records.bin";
+const SECTIONS: &str = "./tests/inputs/sections.md";
+const GRAPHEMES: &str = "./tests/inputs/graphemes.bin";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -41,7 +41,7 @@ fn dies_bad_bytes() -> TestResult {
     let bad = random_string();
     let expected = format!("illegal byte count -- {}", &bad);
     Command::cargo_bin(PRG)?
-        .args(&["-c", &bad, EMPTY])
+        .args(["-c", &bad, EMPTY])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -55,7 +55,7 @@ fn dies_bad_lines() -> TestResult {
     let bad = random_string();
     let expected = format!("illegal line count -- {}", &bad);
     Command::cargo_bin(PRG)?
-        .args(&["-n", &bad, EMPTY])
+        .args(["-n", &bad, EMPTY])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -70,7 +70,7 @@ fn dies_bytes_and_lines() -> TestResult {
                used with '--bytes <BYTES>'";
 
     Command::cargo_bin(PRG)?
-        .args(&["-n", "1", "-c", "2"])
+        .args(["-n", "1", "-c", "2"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(msg));
@@ -78,6 +78,108 @@ fn dies_bytes_and_lines() -> TestResult {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_bytes_too_large() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "99E", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "illegal byte count -- 99E: value too large",
+        ));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_lines_too_large() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "-99999999999999999999", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "illegal line count -- -99999999999999999999: value too large",
+        ));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_until() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--until", "(", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal regex -- ("));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_until_and_bytes() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--until", "^$", "-c", "2", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_chars() -> TestResult {
+    let bad = random_string();
+    let expected = format!("illegal character count -- {}", &bad);
+    Command::cargo_bin(PRG)?
+        .args(["-m", &bad, EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_graphemes_without_chars() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--graphemes", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--chars <CHARS>"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_skip_bytes() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--skip-bytes", "1X", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal byte offset -- 1X"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_skip_bytes_and_lines() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--skip-bytes", "1", "--skip-lines", "1", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn skips_bad_file() -> TestResult {
@@ -93,17 +195,13 @@ fn skips_bad_file() -> TestResult {
 
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> TestResult {
-    // Extra work here due to lossy UTF
-    let mut file = File::open(expected_file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let expected = String::from_utf8_lossy(&buffer);
+    let expected = fs::read(expected_file)?;
 
     Command::cargo_bin(PRG)?
         .args(args)
         .assert()
         .success()
-        .stdout(predicate::eq(&expected.as_bytes() as &[u8]));
+        .stdout(expected);
 
     Ok(())
 }
@@ -114,18 +212,14 @@ fn run_stdin(
     input_file: &str,
     expected_file: &str,
 ) -> TestResult {
-    // Extra work here due to lossy UTF
-    let mut file = File::open(expected_file)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    let expected = String::from_utf8_lossy(&buffer);
-    let input = fs::read_to_string(input_file)?;
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
 
     Command::cargo_bin(PRG)?
         .write_stdin(input)
         .args(args)
         .assert()
-        .stdout(predicate::eq(&expected.as_bytes() as &[u8]));
+        .stdout(expected);
 
     Ok(())
 }
@@ -419,3 +513,347 @@ fn multiple_files_c4() -> TestResult {
         "tests/expected/all.c4.out",
     )
 }
+
+#[test]
+fn empty_n_minus2() -> TestResult {
+    run(&[EMPTY, "-n", "-2"], "tests/expected/empty.txt.n-2.out")
+}
+
+#[test]
+fn empty_c_minus3() -> TestResult {
+    run(&[EMPTY, "-c", "-3"], "tests/expected/empty.txt.c-3.out")
+}
+
+#[test]
+fn one_n_minus2() -> TestResult {
+    run(&[ONE, "-n", "-2"], "tests/expected/one.txt.n-2.out")
+}
+
+#[test]
+fn one_c_minus3() -> TestResult {
+    run(&[ONE, "-c", "-3"], "tests/expected/one.txt.c-3.out")
+}
+
+#[test]
+fn two_n_minus2() -> TestResult {
+    run(&[TWO, "-n", "-2"], "tests/expected/two.txt.n-2.out")
+}
+
+#[test]
+fn two_c_minus3() -> TestResult {
+    run(&[TWO, "-c", "-3"], "tests/expected/two.txt.c-3.out")
+}
+
+#[test]
+fn three_n_minus2() -> TestResult {
+    run(&[THREE, "-n", "-2"], "tests/expected/three.txt.n-2.out")
+}
+
+#[test]
+fn three_c_minus3() -> TestResult {
+    run(&[THREE, "-c", "-3"], "tests/expected/three.txt.c-3.out")
+}
+
+#[test]
+fn ten_n_minus2() -> TestResult {
+    run(&[TEN, "-n", "-2"], "tests/expected/ten.txt.n-2.out")
+}
+
+#[test]
+fn ten_c_minus3() -> TestResult {
+    run(&[TEN, "-c", "-3"], "tests/expected/ten.txt.c-3.out")
+}
+
+#[test]
+fn ten_n_minus2_stdin() -> TestResult {
+    run_stdin(&["-n", "-2"], TEN, "tests/expected/ten.txt.n-2.out")
+}
+
+#[test]
+fn ten_c_minus3_stdin() -> TestResult {
+    run_stdin(&["-c", "-3"], TEN, "tests/expected/ten.txt.c-3.out")
+}
+
+// only as many lines are held back as have been read, however many are asked for
+#[test]
+fn ten_n_minus_huge() -> TestResult {
+    for num in ["-1G", "-1E", "-18446744073709551615"] {
+        Command::cargo_bin(PRG)?
+            .args([TEN, "-n", num])
+            .assert()
+            .success()
+            .stdout("");
+    }
+    Ok(())
+}
+
+#[test]
+fn multiple_files_n_minus2() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-n", "-2"],
+        "tests/expected/all.n-2.out",
+    )
+}
+
+#[test]
+fn multiple_files_c_minus3() -> TestResult {
+    run(
+        &[EMPTY, ONE, TWO, THREE, TEN, "-c", "-3"],
+        "tests/expected/all.c-3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_c1() -> TestResult {
+    run(&[BINARY, "-c", "1"], "tests/expected/binary.bin.c1.out")
+}
+
+#[test]
+fn binary_c260() -> TestResult {
+    run(&[BINARY, "-c", "260"], "tests/expected/binary.bin.c260.out")
+}
+
+#[test]
+fn binary_c260_stdin() -> TestResult {
+    run_stdin(&["-c", "260"], BINARY, "tests/expected/binary.bin.c260.out")
+}
+
+#[test]
+fn binary_c1000() -> TestResult {
+    run(&[BINARY, "-c", "1000"], "tests/expected/binary.bin.c1000.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_c1b() -> TestResult {
+    run(&[BINARY, "-c", "1b"], "tests/expected/binary.bin.c1b.out")
+}
+
+#[test]
+fn binary_c_minus1b() -> TestResult {
+    run(&[BINARY, "-c", "-1b"], "tests/expected/binary.bin.c-1b.out")
+}
+
+#[test]
+fn binary_c1k() -> TestResult {
+    run(&[BINARY, "-c", "1K"], "tests/expected/binary.bin.c1K.out")
+}
+
+#[test]
+fn ten_n1k() -> TestResult {
+    run(&[TEN, "-n", "1K"], "tests/expected/ten.txt.n1K.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_quiet() -> TestResult {
+    run(
+        &["-q", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.q.out",
+    )
+}
+
+#[test]
+fn multiple_files_silent() -> TestResult {
+    run(
+        &["--silent", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.q.out",
+    )
+}
+
+#[test]
+fn multiple_files_verbose_then_quiet() -> TestResult {
+    run(
+        &["-v", "-q", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.q.out",
+    )
+}
+
+#[test]
+fn one_verbose() -> TestResult {
+    run(&["-v", ONE], "tests/expected/one.txt.v.out")
+}
+
+#[test]
+fn one_quiet_then_verbose() -> TestResult {
+    run(&["-q", "--verbose", ONE], "tests/expected/one.txt.v.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn records_z_n2() -> TestResult {
+    run(&["-z", "-n", "2", RECORDS], "tests/expected/records.bin.z.n2.out")
+}
+
+#[test]
+fn records_z_n2_stdin() -> TestResult {
+    run_stdin(
+        &["--zero-terminated", "-n", "2"],
+        RECORDS,
+        "tests/expected/records.bin.z.n2.out",
+    )
+}
+
+#[test]
+fn records_z_n_minus2() -> TestResult {
+    run(
+        &["-z", "-n", "-2", RECORDS],
+        "tests/expected/records.bin.z.n-2.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn sections_until_blank() -> TestResult {
+    run(
+        &["--until", "^$", SECTIONS],
+        "tests/expected/sections.md.until-blank.out",
+    )
+}
+
+#[test]
+fn sections_until_blank_stdin() -> TestResult {
+    run_stdin(
+        &["--until", "^$"],
+        SECTIONS,
+        "tests/expected/sections.md.until-blank.out",
+    )
+}
+
+#[test]
+fn sections_until_blank_inclusive() -> TestResult {
+    run(
+        &["--until", "^$", "--until-inclusive", SECTIONS],
+        "tests/expected/sections.md.until-blank-inclusive.out",
+    )
+}
+
+#[test]
+fn sections_until_rule_n2() -> TestResult {
+    run(
+        &["--until", "^---$", "-n", "2", SECTIONS],
+        "tests/expected/sections.md.until-rule.n2.out",
+    )
+}
+
+#[test]
+fn sections_until_rule_n_minus1() -> TestResult {
+    run(
+        &["--until", "^---$", "-n", "-1", SECTIONS],
+        "tests/expected/sections.md.until-rule.n-1.out",
+    )
+}
+
+#[test]
+fn multiple_files_until_blank_n3() -> TestResult {
+    run(
+        &["--until", "^$", "-n", "3", SECTIONS, TEN],
+        "tests/expected/sections.ten.until-blank.n3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn one_m1() -> TestResult {
+    run(&[ONE, "-m", "1"], "tests/expected/one.txt.m1.out")
+}
+
+#[test]
+fn one_m1_stdin() -> TestResult {
+    run_stdin(&["--chars", "1"], ONE, "tests/expected/one.txt.m1.out")
+}
+
+#[test]
+fn multiple_files_m1() -> TestResult {
+    run(
+        &["-m", "1", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.m1.out",
+    )
+}
+
+#[test]
+fn graphemes_m3() -> TestResult {
+    run(&[GRAPHEMES, "-m", "3"], "tests/expected/graphemes.bin.m3.out")
+}
+
+#[test]
+fn graphemes_m8() -> TestResult {
+    run(&[GRAPHEMES, "-m", "8"], "tests/expected/graphemes.bin.m8.out")
+}
+
+#[test]
+fn graphemes_graphemes_m2() -> TestResult {
+    run(
+        &[GRAPHEMES, "--graphemes", "-m", "2"],
+        "tests/expected/graphemes.bin.graphemes.m2.out",
+    )
+}
+
+#[test]
+fn graphemes_graphemes_m5_stdin() -> TestResult {
+    run_stdin(
+        &["--graphemes", "-m", "5"],
+        GRAPHEMES,
+        "tests/expected/graphemes.bin.graphemes.m5.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn ten_skip_lines3_n2() -> TestResult {
+    run(
+        &["--skip-lines", "3", "-n", "2", TEN],
+        "tests/expected/ten.txt.skip-lines3.n2.out",
+    )
+}
+
+#[test]
+fn ten_skip_lines3_n2_stdin() -> TestResult {
+    run_stdin(
+        &["--skip-lines", "3", "-n", "2"],
+        TEN,
+        "tests/expected/ten.txt.skip-lines3.n2.out",
+    )
+}
+
+#[test]
+fn ten_skip_lines2_n_minus5() -> TestResult {
+    run(
+        &["--skip-lines", "2", "-n", "-5", TEN],
+        "tests/expected/ten.txt.skip-lines2.n-5.out",
+    )
+}
+
+#[test]
+fn ten_skip_bytes4_c7() -> TestResult {
+    run(
+        &["--skip-bytes", "4", "-c", "7", TEN],
+        "tests/expected/ten.txt.skip-bytes4.c7.out",
+    )
+}
+
+#[test]
+fn binary_skip_bytes100_c200() -> TestResult {
+    run(
+        &["--skip-bytes", "100", "-c", "200", BINARY],
+        "tests/expected/binary.bin.skip-bytes100.c200.out",
+    )
+}
+
+#[test]
+fn binary_skip_bytes100_c200_stdin() -> TestResult {
+    run_stdin(
+        &["--skip-bytes", "100", "-c", "200"],
+        BINARY,
+        "tests/expected/binary.bin.skip-bytes100.c200.out",
+    )
+}
+
+#[test]
+fn multiple_files_skip_lines1_n1() -> TestResult {
+    run(
+        &["--skip-lines", "1", "-n", "1", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/all.skip-lines1.n1.out",
+    )
+}
@@ -0,0 +1,240 @@
+use assert_cmd::{cargo::cargo_bin, Command};
+use predicates::prelude::*;
+use std::{
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    process::{self, Output, Stdio},
+    thread,
+    time::Duration,
+};
+use tempfile::TempDir;
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "followr";
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_pid() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--pid", "x1", "./tests/inputs/one.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal pid -- x1"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_sleep_interval() -> TestResult {
+    for bad in ["-1", "x", "inf", "NaN", "1e20"] {
+        Command::cargo_bin(PRG)?
+            .args([&format!("--sleep-interval={}", bad), "./tests/inputs/one.txt"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!("illegal sleep interval -- {}", bad)));
+    }
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_files_remaining() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["./tests/inputs/missing.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no files remaining"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_is_not_followed() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "2"])
+        .write_stdin("one\ntwo\nthree\n")
+        .assert()
+        .success()
+        .stdout("two\nthree\n");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+// gives followr time to notice a change
+fn pause() {
+    thread::sleep(Duration::from_millis(300));
+}
+
+fn append(path: &Path, text: &str) -> TestResult {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+// runs followr in `dir` while `actions` changes the files there, stopping it
+// through --pid once they are done
+fn follow(
+    dir: &TempDir,
+    args: &[&str],
+    actions: impl FnOnce() -> TestResult,
+) -> Result<Output, Box<dyn Error>> {
+    let mut watched = process::Command::new("sleep").arg("30").spawn()?;
+    let follower = process::Command::new(cargo_bin(PRG))
+        .current_dir(dir.path())
+        .args(args)
+        .args(["-s", "0.1", "--pid", &watched.id().to_string()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    pause();
+    let result = actions();
+    pause();
+    watched.kill()?;
+    watched.wait()?;
+    let output = follower.wait_with_output()?;
+    result?;
+    Ok(output)
+}
+
+// --------------------------------------------------
+fn run_appended(args: &[&str]) -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "one\ntwo\nthree\n")?;
+    let output = follow(&dir, args, || {
+        append(&log, "four\n")?;
+        pause();
+        append(&log, "five")?;
+        Ok(())
+    })?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "two\nthree\nfour\nfive");
+
+    Ok(())
+}
+
+#[test]
+fn follows_appended_data() -> TestResult {
+    run_appended(&["-n", "2", "log"])
+}
+
+#[test]
+fn follows_appended_data_polling() -> TestResult {
+    run_appended(&["-n", "2", "--disable-inotify", "log"])
+}
+
+// --------------------------------------------------
+// a burst much larger than what is read at a time comes through whole
+#[test]
+fn follows_large_burst() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "")?;
+    let burst: String = (0..100_000).map(|n| format!("line {}\n", n)).collect();
+    let output = follow(&dir, &["log"], || append(&log, &burst))?;
+    assert_eq!(String::from_utf8(output.stdout)?, burst);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follows_truncated_file() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "a long first line\n")?;
+    let output = follow(&dir, &["-f", "log"], || {
+        fs::write(&log, "new\n")?;
+        Ok(())
+    })?;
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "a long first line\nnew\n"
+    );
+    assert_eq!(String::from_utf8(output.stderr)?, "log: file truncated\n");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follows_rotated_file_by_name() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "old\n")?;
+    let output = follow(&dir, &["-F", "log"], || {
+        fs::rename(&log, dir.path().join("log.1"))?;
+        append(&dir.path().join("log.1"), "last old\n")?;
+        pause();
+        append(&log, "new\n")?;
+        Ok(())
+    })?;
+    assert_eq!(String::from_utf8(output.stdout)?, "old\nlast old\nnew\n");
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("log: has become inaccessible"));
+    assert!(stderr.contains("log: has appeared; following new file"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follows_renamed_file_by_descriptor() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    fs::write(&log, "old\n")?;
+    let output = follow(&dir, &["--follow=descriptor", "log"], || {
+        fs::rename(&log, dir.path().join("log.1"))?;
+        append(&dir.path().join("log.1"), "still old\n")?;
+        append(&log, "new\n")?;
+        Ok(())
+    })?;
+    assert_eq!(String::from_utf8(output.stdout)?, "old\nstill old\n");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn retries_missing_file() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let log = dir.path().join("log");
+    let output = follow(&dir, &["--retry", "log"], || {
+        append(&log, "appeared\n")?;
+        Ok(())
+    })?;
+    assert_eq!(String::from_utf8(output.stdout)?, "appeared\n");
+    assert!(String::from_utf8(output.stderr)?.contains("log: has appeared"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn switches_headers() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+    fs::write(&a, "a1\n")?;
+    fs::write(&b, "b1\n")?;
+    let output = follow(&dir, &["a", "b"], || {
+        append(&a, "a2\n")?;
+        pause();
+        append(&b, "b2\n")?;
+        pause();
+        append(&b, "b3\n")?;
+        Ok(())
+    })?;
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "==> a <==\na1\n\n==> b <==\nb1\n\n==> a <==\na2\n\n==> b <==\nb2\nb3\n"
+    );
+
+    Ok(())
+}
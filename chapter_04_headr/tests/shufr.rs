@@ -0,0 +1,173 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::{error::Error, fs};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "shufr";
+const EMPTY: &str = "./tests/inputs/empty.txt";
+const THREE: &str = "./tests/inputs/three.txt";
+const TEN: &str = "./tests/inputs/ten.txt";
+const RECORDS: &str = "./tests/inputs/records.bin";
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_sample() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--sample", "x", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal sample size -- x"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_seed() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--seed=-1", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal seed -- -1"));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["./tests/inputs/missing.txt", THREE])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(
+            "missing.txt: .* [(]os error 2[)]",
+        )?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn shuffled(args: &[&str]) -> Result<Vec<String>, Box<dyn Error>> {
+    let output = Command::cargo_bin(PRG)?.args(args).output()?;
+    assert!(output.status.success());
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+fn sorted(mut lines: Vec<String>) -> Vec<String> {
+    lines.sort();
+    lines
+}
+
+fn lines_of(files: &[&str]) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut lines = vec![];
+    for file in files {
+        lines.extend(fs::read_to_string(file)?.lines().map(String::from));
+    }
+    Ok(lines)
+}
+
+// --------------------------------------------------
+#[test]
+fn shuffles_every_line() -> TestResult {
+    let lines = shuffled(&[TEN, THREE])?;
+    assert_eq!(sorted(lines), sorted(lines_of(&[TEN, THREE])?));
+
+    Ok(())
+}
+
+#[test]
+fn shuffles_stdin() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--seed", "1"])
+        .write_stdin("only\n")
+        .assert()
+        .success()
+        .stdout("only\n");
+
+    Ok(())
+}
+
+#[test]
+fn seed_repeats_shuffle() -> TestResult {
+    let first = shuffled(&["--seed", "42", TEN])?;
+    assert_eq!(first, shuffled(&["--seed", "42", TEN])?);
+    assert_ne!(first, lines_of(&[TEN])?);
+
+    Ok(())
+}
+
+// the order a seed gives must not change with the version of rand
+#[test]
+fn seed_pins_shuffle() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--seed", "42", TEN])
+        .assert()
+        .success()
+        .stdout("one\nfour\nsix\neight\nten\nfive\nnine\ntwo\nseven\nthree\n");
+    Command::cargo_bin(PRG)?
+        .args(["--seed", "42", "-n", "3", TEN])
+        .assert()
+        .success()
+        .stdout("one\nseven\nthree\n");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn samples_distinct_lines() -> TestResult {
+    let sample = shuffled(&["-n", "4", TEN, THREE])?;
+    assert_eq!(sample.len(), 4);
+    let all = lines_of(&[TEN, THREE])?;
+    assert!(sample.iter().all(|line| all.contains(line)));
+    let mut distinct = sorted(sample);
+    distinct.dedup();
+    assert_eq!(distinct.len(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn seed_repeats_sample() -> TestResult {
+    let first = shuffled(&["--sample", "3", "--seed", "7", TEN])?;
+    assert_eq!(first, shuffled(&["--sample", "3", "--seed", "7", TEN])?);
+
+    Ok(())
+}
+
+#[test]
+fn sample_larger_than_input() -> TestResult {
+    let lines = shuffled(&["--sample", "1K", TEN])?;
+    assert_eq!(sorted(lines), sorted(lines_of(&[TEN])?));
+
+    Ok(())
+}
+
+#[test]
+fn sample_zero() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "0", TEN])
+        .assert()
+        .success()
+        .stdout("");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn shuffles_records() -> TestResult {
+    let output = Command::cargo_bin(PRG)?.args(["-z", RECORDS]).output()?;
+    let mut records: Vec<_> = output.stdout.split(|&b| b == 0).collect();
+    assert_eq!(records.pop(), Some(&b""[..]));
+    records.sort();
+    let expected: Vec<&[u8]> = vec![b"five", b"four", b"one", b"three\nstill three", b"two"];
+    assert_eq!(records, expected);
+
+    Ok(())
+}
@@ -0,0 +1,390 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{distributions::Alphanumeric, Rng};
+use std::{error::Error, fs};
+
+type TestResult = Result<(), Box<dyn Error>>;
+
+const PRG: &str = "tailr";
+const EMPTY: &str = "./tests/inputs/empty.txt";
+const ONE: &str = "./tests/inputs/one.txt";
+const TWO: &str = "./tests/inputs/two.txt";
+const THREE: &str = "./tests/inputs/three.txt";
+const TEN: &str = "./tests/inputs/ten.txt";
+const BINARY: &str = "./tests/inputs/binary.bin";
+const RECORDS: &str = "./tests/inputs/records.bin";
+
+// --------------------------------------------------
+fn random_string() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(7)
+        .map(char::from)
+        .collect()
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename = random_string();
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_bytes() -> TestResult {
+    let bad = random_string();
+    let expected = format!("illegal byte count -- {}", &bad);
+    Command::cargo_bin(PRG)?
+        .args(["-c", &bad, EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_lines() -> TestResult {
+    let bad = random_string();
+    let expected = format!("illegal line count -- +{}", &bad);
+    Command::cargo_bin(PRG)?
+        .args(["-n", &format!("+{}", bad), EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bytes_and_lines() -> TestResult {
+    let msg = "The argument '--lines <LINES>' cannot be \
+               used with '--bytes <BYTES>'";
+
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", "-c", "2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(msg));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> TestResult {
+    let bad = gen_bad_file();
+    let expected = format!("{}: .* [(]os error 2[)]", bad);
+    Command::cargo_bin(PRG)?
+        .args([EMPTY, &bad, ONE])
+        .assert()
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> TestResult {
+    let expected = fs::read(expected_file)?;
+    let input = fs::read(input_file)?;
+
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .args(args)
+        .assert()
+        .stdout(expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> TestResult {
+    run(&[EMPTY], "tests/expected/tailr/empty.txt.out")
+}
+
+#[test]
+fn empty_n3() -> TestResult {
+    run(&[EMPTY, "-n", "3"], "tests/expected/tailr/empty.txt.n3.out")
+}
+
+#[test]
+fn empty_n3_stdin() -> TestResult {
+    run_stdin(&["-n", "3"], EMPTY, "tests/expected/tailr/empty.txt.n3.out")
+}
+
+#[test]
+fn empty_n_plus3() -> TestResult {
+    run(&[EMPTY, "-n", "+3"], "tests/expected/tailr/empty.txt.n+3.out")
+}
+
+#[test]
+fn empty_n_plus3_stdin() -> TestResult {
+    run_stdin(&["-n", "+3"], EMPTY, "tests/expected/tailr/empty.txt.n+3.out")
+}
+
+#[test]
+fn empty_c5() -> TestResult {
+    run(&[EMPTY, "-c", "5"], "tests/expected/tailr/empty.txt.c5.out")
+}
+
+#[test]
+fn empty_c5_stdin() -> TestResult {
+    run_stdin(&["-c", "5"], EMPTY, "tests/expected/tailr/empty.txt.c5.out")
+}
+
+#[test]
+fn empty_c_plus5() -> TestResult {
+    run(&[EMPTY, "-c", "+5"], "tests/expected/tailr/empty.txt.c+5.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn one() -> TestResult {
+    run(&[ONE], "tests/expected/tailr/one.txt.out")
+}
+
+#[test]
+fn one_n3() -> TestResult {
+    run(&[ONE, "-n", "3"], "tests/expected/tailr/one.txt.n3.out")
+}
+
+#[test]
+fn one_n3_stdin() -> TestResult {
+    run_stdin(&["-n", "3"], ONE, "tests/expected/tailr/one.txt.n3.out")
+}
+
+#[test]
+fn one_n_plus3() -> TestResult {
+    run(&[ONE, "-n", "+3"], "tests/expected/tailr/one.txt.n+3.out")
+}
+
+#[test]
+fn one_n_plus3_stdin() -> TestResult {
+    run_stdin(&["-n", "+3"], ONE, "tests/expected/tailr/one.txt.n+3.out")
+}
+
+#[test]
+fn one_c5() -> TestResult {
+    run(&[ONE, "-c", "5"], "tests/expected/tailr/one.txt.c5.out")
+}
+
+#[test]
+fn one_c5_stdin() -> TestResult {
+    run_stdin(&["-c", "5"], ONE, "tests/expected/tailr/one.txt.c5.out")
+}
+
+#[test]
+fn one_c_plus5() -> TestResult {
+    run(&[ONE, "-c", "+5"], "tests/expected/tailr/one.txt.c+5.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn two() -> TestResult {
+    run(&[TWO], "tests/expected/tailr/two.txt.out")
+}
+
+#[test]
+fn two_n3() -> TestResult {
+    run(&[TWO, "-n", "3"], "tests/expected/tailr/two.txt.n3.out")
+}
+
+#[test]
+fn two_n3_stdin() -> TestResult {
+    run_stdin(&["-n", "3"], TWO, "tests/expected/tailr/two.txt.n3.out")
+}
+
+#[test]
+fn two_n_plus3() -> TestResult {
+    run(&[TWO, "-n", "+3"], "tests/expected/tailr/two.txt.n+3.out")
+}
+
+#[test]
+fn two_n_plus3_stdin() -> TestResult {
+    run_stdin(&["-n", "+3"], TWO, "tests/expected/tailr/two.txt.n+3.out")
+}
+
+#[test]
+fn two_c5() -> TestResult {
+    run(&[TWO, "-c", "5"], "tests/expected/tailr/two.txt.c5.out")
+}
+
+#[test]
+fn two_c5_stdin() -> TestResult {
+    run_stdin(&["-c", "5"], TWO, "tests/expected/tailr/two.txt.c5.out")
+}
+
+#[test]
+fn two_c_plus5() -> TestResult {
+    run(&[TWO, "-c", "+5"], "tests/expected/tailr/two.txt.c+5.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn three() -> TestResult {
+    run(&[THREE], "tests/expected/tailr/three.txt.out")
+}
+
+#[test]
+fn three_n3() -> TestResult {
+    run(&[THREE, "-n", "3"], "tests/expected/tailr/three.txt.n3.out")
+}
+
+#[test]
+fn three_n3_stdin() -> TestResult {
+    run_stdin(&["-n", "3"], THREE, "tests/expected/tailr/three.txt.n3.out")
+}
+
+#[test]
+fn three_n_plus3() -> TestResult {
+    run(&[THREE, "-n", "+3"], "tests/expected/tailr/three.txt.n+3.out")
+}
+
+#[test]
+fn three_n_plus3_stdin() -> TestResult {
+    run_stdin(&["-n", "+3"], THREE, "tests/expected/tailr/three.txt.n+3.out")
+}
+
+#[test]
+fn three_c5() -> TestResult {
+    run(&[THREE, "-c", "5"], "tests/expected/tailr/three.txt.c5.out")
+}
+
+#[test]
+fn three_c5_stdin() -> TestResult {
+    run_stdin(&["-c", "5"], THREE, "tests/expected/tailr/three.txt.c5.out")
+}
+
+#[test]
+fn three_c_plus5() -> TestResult {
+    run(&[THREE, "-c", "+5"], "tests/expected/tailr/three.txt.c+5.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn ten() -> TestResult {
+    run(&[TEN], "tests/expected/tailr/ten.txt.out")
+}
+
+#[test]
+fn ten_n3() -> TestResult {
+    run(&[TEN, "-n", "3"], "tests/expected/tailr/ten.txt.n3.out")
+}
+
+#[test]
+fn ten_n3_stdin() -> TestResult {
+    run_stdin(&["-n", "3"], TEN, "tests/expected/tailr/ten.txt.n3.out")
+}
+
+// only as many lines are held as have been read, however many are asked for
+#[test]
+fn ten_n_huge_stdin() -> TestResult {
+    for num in ["1G", "1E", "18446744073709551615"] {
+        run_stdin(&["-n", num], TEN, "tests/expected/tailr/ten.txt.out")?;
+    }
+    Ok(())
+}
+
+#[test]
+fn ten_n_plus3() -> TestResult {
+    run(&[TEN, "-n", "+3"], "tests/expected/tailr/ten.txt.n+3.out")
+}
+
+#[test]
+fn ten_n_plus3_stdin() -> TestResult {
+    run_stdin(&["-n", "+3"], TEN, "tests/expected/tailr/ten.txt.n+3.out")
+}
+
+#[test]
+fn ten_c5() -> TestResult {
+    run(&[TEN, "-c", "5"], "tests/expected/tailr/ten.txt.c5.out")
+}
+
+#[test]
+fn ten_c5_stdin() -> TestResult {
+    run_stdin(&["-c", "5"], TEN, "tests/expected/tailr/ten.txt.c5.out")
+}
+
+#[test]
+fn ten_c_plus5() -> TestResult {
+    run(&[TEN, "-c", "+5"], "tests/expected/tailr/ten.txt.c+5.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_n2() -> TestResult {
+    run(&[BINARY, "-n", "2"], "tests/expected/tailr/binary.bin.n2.out")
+}
+
+#[test]
+fn binary_n2_stdin() -> TestResult {
+    run_stdin(&["-n", "2"], BINARY, "tests/expected/tailr/binary.bin.n2.out")
+}
+
+#[test]
+fn binary_c1b() -> TestResult {
+    run(&[BINARY, "-c", "1b"], "tests/expected/tailr/binary.bin.c1b.out")
+}
+
+#[test]
+fn records_z_n2() -> TestResult {
+    run(&["-z", "-n", "2", RECORDS], "tests/expected/tailr/records.bin.z.n2.out")
+}
+
+#[test]
+fn records_z_n_plus2() -> TestResult {
+    run(
+        &["-z", "-n", "+2", RECORDS],
+        "tests/expected/tailr/records.bin.z.n+2.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files() -> TestResult {
+    run(&[EMPTY, ONE, TWO, THREE, TEN], "tests/expected/tailr/all.out")
+}
+
+#[test]
+fn multiple_files_n0() -> TestResult {
+    run(
+        &["-n", "0", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/tailr/all.n0.out",
+    )
+}
+
+#[test]
+fn multiple_files_n_plus2() -> TestResult {
+    run(
+        &["-n", "+2", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/tailr/all.n+2.out",
+    )
+}
+
+#[test]
+fn multiple_files_quiet_c3() -> TestResult {
+    run(
+        &["-q", "-c", "3", EMPTY, ONE, TWO, THREE, TEN],
+        "tests/expected/tailr/all.q.c3.out",
+    )
+}
@@ -1,10 +1,19 @@
 use clap::{App, Arg};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, ErrorKind, Read};
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
+// how much is read at a time
+const BUFFER_SIZE: usize = 256 * 1024;
+
+// how many bytes at a time are checked for being plain ASCII, which is
+// counted without decoding
+const BLOCK_SIZE: usize = 64;
+
 #[derive(Debug)]
 pub struct Config {
     files: Vec<String>,
@@ -12,9 +21,10 @@ pub struct Config {
     words: bool,
     bytes: bool,
     chars: bool,
+    jobs: usize,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub struct FileInfo {
     num_lines: usize,
     num_words: usize,
@@ -64,8 +74,22 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Show line count")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .value_name("N")
+                .help("Count up to N files at once")
+                .default_value("1"),
+        )
         .get_matches();
 
+    let jobs = matches
+        .value_of("jobs")
+        .map(parse_jobs)
+        .transpose()
+        .map_err(|e| format!("illegal job count -- {}", e))?;
+
     let mut lines = matches.is_present("lines");
     let mut words = matches.is_present("words");
     let mut bytes = matches.is_present("bytes");
@@ -83,9 +107,18 @@ pub fn get_args() -> MyResult<Config> {
         words,
         bytes,
         chars,
+        jobs: jobs.unwrap(),
     })
 }
 
+// --------------------------------------------------
+fn parse_jobs(val: &str) -> MyResult<usize> {
+    match val.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(From::from(val)),
+    }
+}
+
 // --------------------------------------------------
 pub fn run(config: Config) -> MyResult<()> {
     let mut total_lines = 0;
@@ -93,30 +126,45 @@ pub fn run(config: Config) -> MyResult<()> {
     let mut total_bytes = 0;
     let mut total_chars = 0;
 
-    for filename in &config.files {
-        match open(filename) {
-            Err(err) => eprintln!("{}: {}", filename, err),
-            Ok(file) => {
-                if let Ok(info) = count(file) {
-                    println!(
-                        "{}{}{}{}{}",
-                        format_field(info.num_lines, config.lines),
-                        format_field(info.num_words, config.words),
-                        format_field(info.num_bytes, config.bytes),
-                        format_field(info.num_chars, config.chars),
-                        if filename == "-" {
-                            "".to_string()
-                        } else {
-                            format!(" {}", &filename)
-                        },
-                    );
-
-                    total_lines += info.num_lines;
-                    total_words += info.num_words;
-                    total_bytes += info.num_bytes;
-                    total_chars += info.num_chars;
-                }
-            }
+    let mut report = |filename: &str, result: MyResult<FileInfo>| match result {
+        Err(err) => eprintln!("{}: {}", filename, err),
+        Ok(info) => {
+            println!(
+                "{}{}{}{}{}",
+                format_field(info.num_lines, config.lines),
+                format_field(info.num_words, config.words),
+                format_field(info.num_bytes, config.bytes),
+                format_field(info.num_chars, config.chars),
+                if filename == "-" {
+                    "".to_string()
+                } else {
+                    format!(" {}", &filename)
+                },
+            );
+
+            total_lines += info.num_lines;
+            total_words += info.num_words;
+            total_bytes += info.num_bytes;
+            total_chars += info.num_chars;
+        }
+    };
+
+    if config.jobs > 1 {
+        // the files are counted in any order, but shown in the given one
+        let pool = ThreadPoolBuilder::new().num_threads(config.jobs).build()?;
+        let results: Vec<Result<FileInfo, String>> = pool.install(|| {
+            config
+                .files
+                .par_iter()
+                .map(|filename| count_file(filename, &config).map_err(|e| e.to_string()))
+                .collect()
+        });
+        for (filename, result) in config.files.iter().zip(results) {
+            report(filename, result.map_err(From::from));
+        }
+    } else {
+        for filename in &config.files {
+            report(filename, count_file(filename, &config));
         }
     }
 
@@ -134,10 +182,29 @@ pub fn run(config: Config) -> MyResult<()> {
 }
 
 // --------------------------------------------------
-fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+// does no more work than the counts shown need
+fn count_file(filename: &str, config: &Config) -> MyResult<FileInfo> {
+    let file: Box<dyn Read> = match filename {
+        "-" => Box::new(io::stdin()),
+        _ => {
+            let file = File::open(filename)?;
+            // files under /proc and the like have a size of 0 yet are not
+            // empty, so those are read
+            let only_bytes = !(config.lines || config.words || config.chars);
+            let meta = file.metadata()?;
+            if only_bytes && meta.is_file() && meta.len() > 0 {
+                return Ok(FileInfo {
+                    num_bytes: meta.len() as usize,
+                    ..Default::default()
+                });
+            }
+            Box::new(file)
+        }
+    };
+    if config.words || config.chars {
+        count(file)
+    } else {
+        count_lines(file)
     }
 }
 
@@ -151,37 +218,170 @@ fn format_field(value: usize, show: bool) -> String {
 }
 
 // --------------------------------------------------
-pub fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
-    let mut num_lines = 0;
-    let mut num_words = 0;
-    let mut num_bytes = 0;
-    let mut num_chars = 0;
-    let mut line = String::new();
+pub fn count(file: impl Read) -> MyResult<FileInfo> {
+    let mut counter = Counter::default();
+    read_chunks(file, |chunk| counter.update(chunk))?;
+    Ok(counter.finish())
+}
 
+// --------------------------------------------------
+// lines and bytes need no decoding, only the newlines counted
+fn count_lines(file: impl Read) -> MyResult<FileInfo> {
+    let mut info = FileInfo::default();
+    read_chunks(file, |chunk| {
+        info.num_lines += bytecount::count(chunk, b'\n');
+        info.num_bytes += chunk.len();
+    })?;
+    Ok(info)
+}
+
+// --------------------------------------------------
+fn read_chunks(mut file: impl Read, mut handle: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut buffer = vec![0; BUFFER_SIZE];
     loop {
-        let line_bytes = file.read_line(&mut line)?;
-        if line_bytes == 0 {
-            break;
+        match file.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(len) => handle(&buffer[..len]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
         }
-        num_bytes += line_bytes;
-        num_lines += 1;
-        num_words += line.split_whitespace().count();
-        num_chars += line.chars().count();
-        line.clear();
-    }
-
-    Ok(FileInfo {
-        num_lines,
-        num_words,
-        num_bytes,
-        num_chars,
-    })
+    }
+}
+
+// --------------------------------------------------
+// Counts a stream handed over in chunks of bytes, which may split a
+// character, so a partly decoded one is carried over to the next chunk.
+// As with GNU wc, bytes that are not valid UTF-8 are not characters and
+// neither start nor end a word.
+#[derive(Debug, Default)]
+struct Counter {
+    info: FileInfo,
+    in_word: bool,
+    // the code point decoded so far, how many continuation bytes it still
+    // needs and the smallest value its length may encode
+    partial: u32,
+    needed: u8,
+    min: u32,
+}
+
+impl Counter {
+    fn update(&mut self, chunk: &[u8]) {
+        self.info.num_bytes += chunk.len();
+        // a newline byte is never part of a longer sequence
+        self.info.num_lines += bytecount::count(chunk, b'\n');
+        for block in chunk.chunks(BLOCK_SIZE) {
+            if block.is_ascii() {
+                // which cuts short any sequence left from before
+                self.needed = 0;
+                self.update_ascii(block);
+            } else {
+                block.iter().for_each(|&byte| self.update_byte(byte));
+            }
+        }
+    }
+
+    fn update_ascii(&mut self, block: &[u8]) {
+        self.info.num_chars += block.len();
+        let controls = block.iter().fold(false, |found, &byte| {
+            found | (byte < b' ' && !(b'\t'..=b'\r').contains(&byte)) | (byte == 0x7F)
+        });
+        if controls {
+            for &byte in block {
+                self.info.num_words += ascii_word_start(byte, &mut self.in_word) as usize;
+            }
+            return;
+        }
+        // with no control characters to pass over, each byte is either
+        // whitespace or part of a word, and the loop needs no branches
+        let mut after_space = !self.in_word as usize;
+        let mut num_words = 0;
+        for &byte in block {
+            let printable = (byte > b' ') as usize;
+            num_words += printable & after_space;
+            after_space = printable ^ 1;
+        }
+        self.info.num_words += num_words;
+        self.in_word = after_space == 0;
+    }
+
+    fn update_byte(&mut self, byte: u8) {
+        if self.needed > 0 {
+            if byte & 0xC0 == 0x80 {
+                self.partial = self.partial << 6 | (byte & 0x3F) as u32;
+                self.needed -= 1;
+                if self.needed == 0 && self.partial >= self.min {
+                    // surrogates and values past U+10FFFF are rejected
+                    if let Some(c) = char::from_u32(self.partial) {
+                        self.add_char(c);
+                    }
+                }
+                return;
+            }
+            // the sequence was cut short, and this byte starts afresh
+            self.needed = 0;
+        }
+        match byte {
+            0x00..=0x7F => {
+                self.info.num_chars += 1;
+                self.info.num_words += ascii_word_start(byte, &mut self.in_word) as usize;
+            }
+            0xC0..=0xDF => self.start(byte & 0x1F, 1, 0x80),
+            0xE0..=0xEF => self.start(byte & 0x0F, 2, 0x800),
+            0xF0..=0xF7 => self.start(byte & 0x07, 3, 0x10000),
+            // a stray continuation byte or one UTF-8 never uses
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, bits: u8, needed: u8, min: u32) {
+        self.partial = bits as u32;
+        self.needed = needed;
+        self.min = min;
+    }
+
+    fn add_char(&mut self, c: char) {
+        self.info.num_chars += 1;
+        if is_separator(c) {
+            self.in_word = false;
+        } else if !c.is_control() && !self.in_word {
+            self.in_word = true;
+            self.info.num_words += 1;
+        }
+    }
+
+    // a sequence still incomplete at the end is dropped like any invalid one
+    fn finish(self) -> FileInfo {
+        self.info
+    }
+}
+
+// --------------------------------------------------
+// does for an ASCII byte what `add_char` does, but without branching:
+// whitespace ends a word, the other printable characters are part of one
+// and control characters are passed over
+fn ascii_word_start(byte: u8, in_word: &mut bool) -> bool {
+    let space = byte == b' ' || (b'\t'..=b'\r').contains(&byte);
+    let printable = (b'!'..=b'~').contains(&byte);
+    let start = printable & !*in_word;
+    *in_word = printable | (*in_word & !space);
+    start
+}
+
+// --------------------------------------------------
+// GNU wc also splits words at the non-breaking spaces, but not at NEL,
+// which it takes for a control character like any other
+fn is_separator(c: char) -> bool {
+    match c {
+        '\u{85}' => false,
+        '\u{a0}' | '\u{2007}' | '\u{202f}' | '\u{2060}' => true,
+        _ => c.is_whitespace(),
+    }
 }
 
 // --------------------------------------------------
 #[cfg(test)]
 mod tests {
-    use super::{count, format_field, FileInfo};
+    use super::{count, count_lines, format_field, Counter, FileInfo, BLOCK_SIZE};
     use std::io::Cursor;
 
     #[test]
@@ -198,6 +398,62 @@ mod tests {
         assert_eq!(info.unwrap(), expected);
     }
 
+    #[test]
+    fn test_count_invalid_utf8() {
+        // invalid bytes neither count as characters nor split words
+        let text = b"caf\xc3\xa9 \xff\xfe na\xefve\n\xed\xa0\x80 \xc0\xafend\xe2";
+        let info = count(Cursor::new(&text[..]));
+        assert!(info.is_ok());
+        let expected = FileInfo {
+            num_lines: 1,
+            num_words: 3,
+            num_chars: 15,
+            num_bytes: 25,
+        };
+        assert_eq!(info.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_count_split_chunks() {
+        let text = "Frétt hefir öld óvu,\u{a0}þá\u{85}er 🦀\n";
+        let whole = count(Cursor::new(text)).unwrap();
+        assert_eq!(whole.num_words, 6);
+        assert_eq!(whole.num_chars, text.chars().count());
+
+        // long enough for both ASCII blocks and ones with characters,
+        // sometimes split, to be decoded
+        let long = format!("{}\x01{}", "x ".repeat(40), text).repeat(7);
+        assert!(long.len() > 4 * BLOCK_SIZE);
+        let expected = FileInfo {
+            num_lines: 7,
+            num_words: 7 * 46,
+            num_chars: long.chars().count(),
+            num_bytes: long.len(),
+        };
+        for text in [text, &long] {
+            let whole = count(Cursor::new(text)).unwrap();
+            for size in 1..5 {
+                let mut counter = Counter::default();
+                for chunk in text.as_bytes().chunks(size) {
+                    counter.update(chunk);
+                }
+                assert_eq!(counter.finish(), whole);
+            }
+        }
+        assert_eq!(count(Cursor::new(&long)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_count_lines() {
+        let text = b"one\ntwo\n\xff three";
+        let expected = FileInfo {
+            num_lines: 2,
+            num_bytes: text.len(),
+            ..Default::default()
+        };
+        assert_eq!(count_lines(Cursor::new(&text[..])).unwrap(), expected);
+    }
+
     #[test]
     fn test_format_field() {
         assert_eq!(format_field(1, false), "");
@@ -9,6 +9,7 @@ const PRG: &str = "wcr";
 const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const ATLAMAL: &str = "tests/inputs/atlamal.txt";
+const INVALID: &str = "tests/inputs/invalid.bin";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -29,7 +30,7 @@ fn gen_bad_file() -> String {
 #[test]
 fn dies_chars_and_bytes() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&["-m", "-c"])
+        .args(["-m", "-c"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(
@@ -212,3 +213,68 @@ fn test_all_words_lines() -> TestResult {
 fn test_all_bytes_lines() -> TestResult {
     run(&["-cl", EMPTY, FOX, ATLAMAL], "tests/expected/all.cl.out")
 }
+
+// --------------------------------------------------
+// the counts are those of GNU wc in a UTF-8 locale, where invalid bytes
+// are not characters and neither start nor end a word
+#[test]
+fn invalid_utf8() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-lwm", INVALID])
+        .assert()
+        .success()
+        .stdout(format!("       2       8      32 {}\n", INVALID));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_utf8_stdin() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .write_stdin(fs::read(INVALID)?)
+        .assert()
+        .success()
+        .stdout("       2       8      51\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn reports_unreadable_file() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([FOX, "tests/inputs", EMPTY])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(
+            "tests/inputs: .* [(]os error 21[)]",
+        )?)
+        .stdout(format!(
+            "{}{}       1       9      48 total\n",
+            fs::read_to_string("tests/expected/fox.txt.out")?,
+            fs::read_to_string("tests/expected/empty.txt.out")?,
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_jobs() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--jobs", "0", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("illegal job count -- 0"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_jobs() -> TestResult {
+    run(&["-j", "3", EMPTY, FOX, ATLAMAL], "tests/expected/all.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_chars_jobs() -> TestResult {
+    run(&["-m", "-j", "2", EMPTY, FOX, ATLAMAL], "tests/expected/all.m.out")
+}